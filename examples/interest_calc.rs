@@ -1,3 +1,4 @@
+use hourglass_rs::calendar::DayCount;
 use hourglass_rs::{SafeTimeProvider, TimeSource};
 use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
@@ -5,11 +6,15 @@ use std::sync::Arc;
 /// Interest calculator service
 struct InterestCalculator {
     time_provider: SafeTimeProvider,
+    day_count: DayCount,
 }
 
 impl InterestCalculator {
     pub fn new(time_provider: SafeTimeProvider) -> Self {
-        Self { time_provider }
+        Self {
+            time_provider,
+            day_count: DayCount::Actual365Fixed,
+        }
     }
     
     /// Calculate simple interest accrued between last accrual and now
@@ -20,8 +25,8 @@ impl InterestCalculator {
         last_accrual: DateTime<Utc>,
     ) -> (f64, DateTime<Utc>) {
         let now = self.time_provider.now();
-        let days = (now - last_accrual).num_days() as f64;
-        let interest = principal * annual_rate * days / 365.0;
+        let years = self.day_count.year_fraction(last_accrual, now);
+        let interest = principal * annual_rate * years;
         (interest, now)
     }
     
@@ -34,7 +39,7 @@ impl InterestCalculator {
         last_accrual: DateTime<Utc>,
     ) -> (f64, f64, DateTime<Utc>) {
         let now = self.time_provider.now();
-        let years = (now - last_accrual).num_days() as f64 / 365.0;
+        let years = self.day_count.year_fraction(last_accrual, now);
         let rate_per_period = annual_rate / compounds_per_year as f64;
         let periods = compounds_per_year as f64 * years;
         
@@ -61,7 +66,7 @@ impl InterestAccrualScheduler {
     }
     
     /// Run daily interest accrual at 2 AM
-    pub async fn run_daily_accruals(&self, accounts: &mut Vec<Account>) {
+    pub async fn run_daily_accruals(&self, accounts: &mut [Account]) {
         loop {
             // Calculate next 2 AM
             let now = self.time_provider.now();
@@ -186,7 +191,7 @@ async fn main() {
     let calculator = InterestCalculator::new(time.clone());
     
     // Manually calculate to show final state
-    for (_i, initial_account) in accounts.iter().enumerate() {
+    for initial_account in accounts.iter() {
         let (simple_interest, _) = calculator.calculate_interest(
             initial_account.balance,
             initial_account.interest_rate,
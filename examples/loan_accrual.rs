@@ -1,10 +1,12 @@
-use hourglass_rs::{SafeTimeProvider, TimeSource};
-use chrono::{DateTime, Duration, Utc, Datelike};
+use hourglass_rs::calendar::DayCount;
+use hourglass_rs::{SafeTimeProvider, Schedule, TimeSource};
+use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 struct LoanTerms {
     annual_rate: f64,
+    day_count: DayCount,
     accrual_interval: AccrualInterval,
     accrual_cycle_interval: AccrualCycleInterval,
     one_time_fee_rate: f64,
@@ -53,8 +55,9 @@ impl Loan {
         }
     }
     
-    fn daily_rate(&self) -> f64 {
-        self.terms.annual_rate / 365.0 / 100.0
+    fn interest_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+        let years = self.terms.day_count.year_fraction(start, end);
+        self.outstanding_principal() * self.terms.annual_rate / 100.0 * years
     }
     
     fn outstanding_principal(&self) -> f64 {
@@ -98,7 +101,7 @@ impl LoanAccrualEngine {
             if matches!(loan.terms.accrual_interval, AccrualInterval::EndOfDay) {
                 let days_since_last = (now - loan.last_accrual).num_days();
                 if days_since_last >= 1 {
-                    let interest = loan.outstanding_principal() * loan.daily_rate() * days_since_last as f64;
+                    let interest = loan.interest_between(loan.last_accrual, now);
                     loan.accrued_interest += interest;
                     loan.last_accrual = now;
                     
@@ -111,12 +114,12 @@ impl LoanAccrualEngine {
     
     async fn run_monthly_cycle_close(&mut self) {
         let now = self.time_provider.now();
+        let month_end: Schedule = "0 0 L * *".parse().unwrap();
         
         for loan in self.loans.values_mut() {
             if matches!(loan.terms.accrual_cycle_interval, AccrualCycleInterval::EndOfMonth) {
                 // Check if we're at month end
-                let tomorrow = now + Duration::days(1);
-                if now.month() != tomorrow.month() {
+                if month_end.includes(now) {
                     println!("  Loan {}: Month-end cycle close", loan.id);
                     println!("    Interest due: ${:.2}", loan.accrued_interest - loan.paid_interest);
                     loan.last_cycle_close = now;
@@ -134,10 +137,13 @@ impl LoanAccrualEngine {
     }
     
     async fn simulate_until(&mut self, end_date: DateTime<Utc>) {
+        // Daily at 00:00 UTC
+        let daily: Schedule = "0 0 * * *".parse().unwrap();
+        
         while self.time_provider.now() < end_date {
             self.run_daily_accruals().await;
             self.run_monthly_cycle_close().await;
-            self.time_provider.wait(Duration::days(1)).await;
+            daily.wait_next(&self.time_provider).await;
         }
     }
 }
@@ -155,6 +161,7 @@ async fn main() {
     // Create loan terms matching the example
     let terms = LoanTerms {
         annual_rate: 12.0,
+        day_count: DayCount::Actual365Fixed,
         accrual_interval: AccrualInterval::EndOfDay,
         accrual_cycle_interval: AccrualCycleInterval::EndOfMonth,
         one_time_fee_rate: 5.0,
@@ -192,8 +199,8 @@ async fn main() {
     for loan in engine.loans.values() {
         println!("Loan {}: Total accrued interest: ${:.2}", loan.id, loan.accrued_interest);
         
-        let days_elapsed = (time.now() - loan.disbursed_at).num_days();
-        let expected_interest = 5_000.0 + (100_000.0 * 0.12 / 365.0 * days_elapsed as f64);
+        let years = DayCount::Actual365Fixed.year_fraction(loan.disbursed_at, time.now());
+        let expected_interest = 5_000.0 + 100_000.0 * 0.12 * years;
         println!("Expected interest: ${:.2}", expected_interest);
         
         // Check overdue status
@@ -219,6 +226,7 @@ mod tests {
         
         let terms = LoanTerms {
             annual_rate: 12.0,
+            day_count: DayCount::Actual365Fixed,
             accrual_interval: AccrualInterval::EndOfDay,
             accrual_cycle_interval: AccrualCycleInterval::EndOfMonth,
             one_time_fee_rate: 5.0,
@@ -250,6 +258,7 @@ mod tests {
         
         let terms = LoanTerms {
             annual_rate: 12.0,
+            day_count: DayCount::Actual365Fixed,
             accrual_interval: AccrualInterval::EndOfDay,
             accrual_cycle_interval: AccrualCycleInterval::EndOfMonth,
             one_time_fee_rate: 0.0,
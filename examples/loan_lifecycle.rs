@@ -1,15 +1,16 @@
-use hourglass_rs::{SafeTimeProvider, TimeSource};
-use chrono::{DateTime, Duration, Utc, Datelike, NaiveDate, Timelike};
+use hourglass_rs::{calendar, SafeTimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
 
 #[derive(Debug, Clone)]
 enum LoanStatus {
     Active,
     PaymentDue,
     Overdue,
+    #[allow(dead_code)]
     Liquidated,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct LoanLifecycle {
     loan_id: String,
     facility: f64,
@@ -28,14 +29,16 @@ struct LoanLifecycle {
     status: LoanStatus,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct Payment {
     date: DateTime<Utc>,
     amount: f64,
     payment_type: PaymentType,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
 enum PaymentType {
     Interest,
     Principal,
@@ -44,7 +47,7 @@ enum PaymentType {
 
 impl LoanLifecycle {
     fn new(loan_id: String, facility: f64, disbursed_at: DateTime<Utc>, duration_months: i32) -> Self {
-        let maturity_date = Self::add_months(disbursed_at, duration_months);
+        let maturity_date = calendar::add_months(disbursed_at, duration_months);
         
         Self {
             loan_id,
@@ -59,42 +62,8 @@ impl LoanLifecycle {
         }
     }
     
-    // Add months handling month-end properly
-    fn add_months(date: DateTime<Utc>, months: i32) -> DateTime<Utc> {
-        let naive = date.naive_utc();
-        let year = naive.year();
-        let month = naive.month();
-        
-        let total_months = month as i32 + months;
-        let new_year = year + (total_months - 1) / 12;
-        let new_month = ((total_months - 1) % 12 + 1) as u32;
-        
-        // Handle month-end dates
-        let new_day = naive.day().min(Self::days_in_month(new_year, new_month));
-        
-        NaiveDate::from_ymd_opt(new_year, new_month, new_day)
-            .unwrap()
-            .and_hms_opt(naive.hour(), naive.minute(), naive.second())
-            .unwrap()
-            .and_utc()
-    }
-    
-    fn days_in_month(year: i32, month: u32) -> u32 {
-        match month {
-            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
-            4 | 6 | 9 | 11 => 30,
-            2 => if Self::is_leap_year(year) { 29 } else { 28 },
-            _ => panic!("Invalid month"),
-        }
-    }
-    
-    fn is_leap_year(year: i32) -> bool {
-        (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
-    }
-    
     fn is_month_end(&self, date: DateTime<Utc>) -> bool {
-        let tomorrow = date + Duration::days(1);
-        date.month() != tomorrow.month()
+        calendar::is_last_day_of_month(date.date_naive())
     }
     
     fn daily_interest_rate(&self) -> f64 {
@@ -118,12 +87,9 @@ impl LoanLifecycle {
             self.last_cycle_close_date = date;
             
             // Update status based on payment due
-            match self.status {
-                LoanStatus::Active => {
-                    self.status = LoanStatus::PaymentDue;
-                    println!("    Status: Payment Due (0 days grace period)");
-                }
-                _ => {}
+            if let LoanStatus::Active = self.status {
+                self.status = LoanStatus::PaymentDue;
+                println!("    Status: Payment Due (0 days grace period)");
             }
         }
     }
@@ -137,10 +103,10 @@ impl LoanLifecycle {
         
         self.accrued_interest = (self.accrued_interest - amount).max(0.0);
         
-        if matches!(self.status, LoanStatus::PaymentDue | LoanStatus::Overdue) {
-            if self.accrued_interest == 0.0 {
-                self.status = LoanStatus::Active;
-            }
+        if matches!(self.status, LoanStatus::PaymentDue | LoanStatus::Overdue)
+            && self.accrued_interest == 0.0
+        {
+            self.status = LoanStatus::Active;
         }
     }
     
@@ -245,29 +211,63 @@ async fn main() {
     }
     
     println!("\nTotal interest accrued in February: ${:.2}", loan2.accrued_interest);
+    
+    // Test 3: Branch at the first month end and try both outcomes
+    println!("\n\nTest 3: Paid vs unpaid from the same month end\n");
+    let time3 = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-31T00:00:00Z".parse().unwrap())
+    );
+    let control3 = time3.test_control().unwrap();
+    
+    let mut loan3 = LoanLifecycle::new(
+        "LOAN-BRANCH-001".to_string(),
+        100_000.0,
+        time3.now(),
+        3,
+    );
+    time3.wait_until("2024-02-29T00:00:00Z".parse().unwrap()).await;
+    loan3.accrue_daily_interest(time3.now());
+    loan3.process_month_end(time3.now());
+    
+    let checkpoint = control3.snapshot();
+    let saved_loan = loan3.clone();
+    
+    // Month-end path: the borrower pays
+    loan3.make_payment(loan3.accrued_interest, time3.now());
+    time3.wait(Duration::days(60)).await;
+    loan3.update_overdue_status(time3.now());
+    println!("Paid at month end, status on {}: {:?}", time3.now().format("%Y-%m-%d"), loan3.status);
+    
+    // Rewind and take the overdue path
+    control3.restore(&checkpoint);
+    let mut loan3 = saved_loan;
+    time3.wait(Duration::days(60)).await;
+    loan3.update_overdue_status(time3.now());
+    println!("Unpaid, status on {}: {:?}", time3.now().format("%Y-%m-%d"), loan3.status);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
     
     #[test]
     fn test_add_months_edge_cases() {
         // Jan 31 + 1 month = Feb 29 (leap year)
         let jan31 = "2024-01-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
-        let feb29 = LoanLifecycle::add_months(jan31, 1);
+        let feb29 = calendar::add_months(jan31, 1);
         assert_eq!(feb29.day(), 29);
         assert_eq!(feb29.month(), 2);
         
         // Jan 31 + 1 month = Feb 28 (non-leap year)
         let jan31_2023 = "2023-01-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
-        let feb28 = LoanLifecycle::add_months(jan31_2023, 1);
+        let feb28 = calendar::add_months(jan31_2023, 1);
         assert_eq!(feb28.day(), 28);
         assert_eq!(feb28.month(), 2);
         
         // March 31 + 1 month = April 30
         let mar31 = "2024-03-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
-        let apr30 = LoanLifecycle::add_months(mar31, 1);
+        let apr30 = calendar::add_months(mar31, 1);
         assert_eq!(apr30.day(), 30);
         assert_eq!(apr30.month(), 4);
     }
@@ -1,10 +1,11 @@
-use hourglass_rs::{SafeTimeProvider, TimeSource};
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
 use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 #[derive(Debug, Clone)]
 struct CollateralTerms {
+    #[allow(dead_code)]
     initial_cvl: f64,      // 140% - Initial collateral value to loan ratio
     margin_call_cvl: f64,  // 125% - Trigger margin call
     liquidation_cvl: f64,  // 105% - Force liquidation
@@ -16,6 +17,7 @@ struct CollateralPosition {
     loan_amount: f64,
     collateral_value: f64,
     terms: CollateralTerms,
+    #[allow(dead_code)]
     created_at: DateTime<Utc>,
     margin_call_sent: Option<DateTime<Utc>>,
     liquidated: bool,
@@ -107,15 +109,16 @@ impl MarginMonitor {
     }
     
     async fn run_continuous_monitoring(&self, check_interval: Duration) {
+        let mut interval = self.time_provider.interval(check_interval);
         loop {
+            interval.tick().await;
             println!("\nMonitoring positions at {}", self.time_provider.now());
             self.monitor_positions().await;
-            self.time_provider.wait(check_interval).await;
         }
     }
 }
 
-#[tokio::main]
+#[tokio::main(flavor = "current_thread")]
 async fn main() {
     println!("=== Margin Monitoring Simulation ===\n");
     
@@ -124,6 +127,9 @@ async fn main() {
     );
     let control = time.test_control().expect("Should be in test mode");
     
+    // Jump straight to the next pending deadline whenever every task is waiting
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
     // Create monitor
     let monitor = MarginMonitor::new(time.clone());
     
@@ -165,32 +171,21 @@ async fn main() {
         monitor_clone.run_continuous_monitoring(Duration::hours(1)).await;
     });
     
-    // Simulate market events
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    
-    // Hour 1: Small decline
-    control.advance(Duration::hours(1));
-    monitor.update_collateral_value("LOAN-001", 130_000.0).await;  // 130% CVL
-    monitor.update_collateral_value("LOAN-002", 145_000.0).await;  // 145% CVL
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    
-    // Hour 2: LOAN-001 hits margin call threshold
-    control.advance(Duration::hours(1));
-    monitor.update_collateral_value("LOAN-001", 120_000.0).await;  // 120% CVL - Margin call!
-    monitor.update_collateral_value("LOAN-002", 140_000.0).await;  // 140% CVL
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    
-    // Hour 3: Market recovers slightly
-    control.advance(Duration::hours(1));
-    monitor.update_collateral_value("LOAN-001", 122_000.0).await;  // 122% CVL
-    monitor.update_collateral_value("LOAN-002", 142_000.0).await;  // 142% CVL
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    
-    // Hour 4: LOAN-001 crashes to liquidation
-    control.advance(Duration::hours(1));
-    monitor.update_collateral_value("LOAN-001", 104_000.0).await;  // 104% CVL - Liquidation!
-    monitor.update_collateral_value("LOAN-002", 135_000.0).await;  // 135% CVL
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    // Market moves at half past each hour; the monitor checks on the hour.
+    // Each wait below lets the clock run forward on its own once the monitor is idle.
+    let market_moves = [
+        (130_000.0, 145_000.0), // Hour 1: Small decline
+        (120_000.0, 140_000.0), // Hour 2: LOAN-001 hits margin call threshold
+        (122_000.0, 142_000.0), // Hour 3: Market recovers slightly
+        (104_000.0, 135_000.0), // Hour 4: LOAN-001 crashes to liquidation
+    ];
+    time.wait(Duration::minutes(30)).await;
+    for (loan1_value, loan2_value) in market_moves {
+        println!("\nMarket update at {}", time.now());
+        monitor.update_collateral_value("LOAN-001", loan1_value).await;
+        monitor.update_collateral_value("LOAN-002", loan2_value).await;
+        time.wait(Duration::hours(1)).await;
+    }
     
     // Cancel monitoring
     monitor_handle.abort();
@@ -207,7 +202,8 @@ async fn main() {
     }
     
     println!("\n=== Time Statistics ===");
-    println!("Total monitoring duration: {} hours", control.wait_call_count());
+    println!("Final time: {}", time.now());
+    println!("Wait calls across monitor and market: {}", control.wait_call_count());
 }
 
 #[cfg(test)]
@@ -0,0 +1,372 @@
+//! Assertions about time behaviour in tests
+//!
+//! The macros panic with a readable timeline of the waits and clock changes
+//! seen by the test clock, so a failure shows what actually happened:
+//!
+//! ```rust
+//! use hourglass_rs::{SafeTimeProvider, TimeSource};
+//! use hourglass_rs::assert::{hours, minutes};
+//! use hourglass_rs::{assert_no_pending_timers, assert_now, assert_waited};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let time = SafeTimeProvider::new(TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap()));
+//!     let control = time.test_control().unwrap();
+//!
+//!     for _ in 0..3 {
+//!         time.wait_labeled("accrual", hours(1)).await;
+//!     }
+//!     time.wait(minutes(5)).await;
+//!
+//!     assert_waited!(control, "accrual", times = 3, total = hours(3));
+//!     assert_waited!(control, times = 4);
+//!     assert_no_pending_timers!(control);
+//!     assert_now!(time, "2024-01-01T03:05:00Z");
+//! }
+//! ```
+//!
+//! A failing assertion reports something like:
+//!
+//! ```text
+//! assert_waited! failed: expected 4 waits labelled "accrual", found 3
+//! timeline (clock now at 2024-01-01T04:05:00Z):
+//!   2024-01-01T00:00:00Z  wait 1h "accrual" until 2024-01-01T01:00:00Z, done at 2024-01-01T01:00:00Z
+//!   2024-01-01T01:00:00Z  wait 1h "accrual" until 2024-01-01T02:00:00Z, done at 2024-01-01T02:00:00Z
+//!   2024-01-01T02:00:00Z  wait 1h "accrual" until 2024-01-01T03:00:00Z, done at 2024-01-01T03:00:00Z
+//!   2024-01-01T03:00:00Z  wait 5m until 2024-01-01T03:05:00Z, done at 2024-01-01T03:05:00Z
+//!   2024-01-01T03:05:00Z  advance 1h to 2024-01-01T04:05:00Z
+//! ```
+
+use crate::control::TimeControl;
+use crate::safe::SafeTimeProvider;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::fmt::Write;
+
+/// Most recent timeline entries shown in a failure message
+const TIMELINE_LIMIT: usize = 50;
+
+/// Shorthand for [`Duration::days`]
+pub fn days(days: i64) -> Duration {
+    Duration::days(days)
+}
+
+/// Shorthand for [`Duration::hours`]
+pub fn hours(hours: i64) -> Duration {
+    Duration::hours(hours)
+}
+
+/// Shorthand for [`Duration::minutes`]
+pub fn minutes(minutes: i64) -> Duration {
+    Duration::minutes(minutes)
+}
+
+/// Shorthand for [`Duration::seconds`]
+pub fn seconds(seconds: i64) -> Duration {
+    Duration::seconds(seconds)
+}
+
+/// Shorthand for [`Duration::milliseconds`]
+pub fn milliseconds(milliseconds: i64) -> Duration {
+    Duration::milliseconds(milliseconds)
+}
+
+/// A time to compare against, either a [`DateTime<Utc>`] or an RFC 3339 string
+pub trait ExpectedTime {
+    /// Convert to a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if a string is not a valid RFC 3339 timestamp.
+    fn into_time(self) -> DateTime<Utc>;
+}
+
+impl ExpectedTime for DateTime<Utc> {
+    fn into_time(self) -> DateTime<Utc> {
+        self
+    }
+}
+
+impl ExpectedTime for &str {
+    fn into_time(self) -> DateTime<Utc> {
+        match DateTime::parse_from_rfc3339(self) {
+            Ok(time) => time.with_timezone(&Utc),
+            Err(e) => panic!("invalid expected time '{}': {}", self, e),
+        }
+    }
+}
+
+impl ExpectedTime for &String {
+    fn into_time(self) -> DateTime<Utc> {
+        self.as_str().into_time()
+    }
+}
+
+impl ExpectedTime for String {
+    fn into_time(self) -> DateTime<Utc> {
+        self.as_str().into_time()
+    }
+}
+
+/// Check the waits labelled `label` (or all waits when `None`)
+///
+/// With neither `times` nor `total`, at least one wait is expected. Used by
+/// [`assert_waited!`](crate::assert_waited).
+///
+/// All waits are counted from the clock's running totals. Labelled waits
+/// can only be counted from the wait history, so once it has dropped old
+/// entries a failure says so.
+pub fn check_waited(
+    control: &TimeControl,
+    label: Option<&str>,
+    times: Option<usize>,
+    total: Option<Duration>,
+) -> Result<(), String> {
+    let (count, waited) = match label {
+        Some(label) => {
+            let waits = control.waits_for(label);
+            let waited = waits.iter().fold(Duration::zero(), |sum, wait| sum + wait.duration);
+            (waits.len(), waited)
+        }
+        None => (control.wait_call_count(), control.total_waited()),
+    };
+    let what = match label {
+        Some(label) => format!("waits labelled {:?}", label),
+        None => "waits".to_string(),
+    };
+
+    let mut problems = Vec::new();
+    match times {
+        Some(times) if count != times => {
+            problems.push(format!("expected {} {}, found {}", times, what, count));
+        }
+        None if total.is_none() && count == 0 => {
+            problems.push(format!("expected at least one of the {}, found none", what));
+        }
+        _ => {}
+    }
+    if let Some(total) = total.filter(|total| *total != waited) {
+        problems.push(format!(
+            "expected {} to total {}, found {}",
+            what,
+            format_duration(total),
+            format_duration(waited)
+        ));
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let kept = control.wait_history().len();
+    if label.is_some() && kept < control.wait_call_count() {
+        problems.push(format!(
+            "only the last {} of {} waits are still in the history",
+            kept,
+            control.wait_call_count()
+        ));
+    }
+    Err(format!("{}\n{}", problems.join("\n"), timeline(control)))
+}
+
+/// Check that no timer is parked on the test clock
+///
+/// Used by [`assert_no_pending_timers!`](crate::assert_no_pending_timers).
+pub fn check_no_pending_timers(control: &TimeControl) -> Result<(), String> {
+    let pending = control.pending_timers();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!("expected no pending timers, found {}:", pending.len());
+    for timer in &pending {
+        let label = match &timer.label {
+            Some(label) => format!(" {:?}", label),
+            None => String::new(),
+        };
+        let _ = write!(
+            message,
+            "\n  due {}{}, created at {}",
+            format_time(timer.deadline),
+            label,
+            format_time(timer.created_at)
+        );
+    }
+    Err(format!("{}\n{}", message, timeline(control)))
+}
+
+/// Check that `provider` reads `expected`
+///
+/// Used by [`assert_now!`](crate::assert_now).
+pub fn check_now(provider: &SafeTimeProvider, expected: impl ExpectedTime) -> Result<(), String> {
+    let expected = expected.into_time();
+    let now = provider.now();
+    if now == expected {
+        return Ok(());
+    }
+    let mut message = format!(
+        "expected the clock to read {}, but it reads {} ({} {})",
+        format_time(expected),
+        format_time(now),
+        format_duration((now - expected).abs()),
+        if now > expected { "late" } else { "early" }
+    );
+    if let Some(control) = provider.test_control() {
+        message.push('\n');
+        message.push_str(&timeline(&control));
+    }
+    Err(message)
+}
+
+/// Render the waits and clock changes seen by the test clock, oldest first
+///
+/// Only the most recent entries are shown.
+pub fn timeline(control: &TimeControl) -> String {
+    let waits = control.wait_history();
+    let changes = control.clock_changes();
+
+    let mut entries = Vec::with_capacity(waits.len() + changes.len());
+    let mut changes = changes.into_iter().peekable();
+    for (index, wait) in waits.iter().enumerate() {
+        while let Some(change) = changes.next_if(|change| change.waits_before <= index) {
+            entries.push(format_change(change.from, change.to, change.set));
+        }
+        let label = match &wait.label {
+            Some(label) => format!(" {:?}", label),
+            None => String::new(),
+        };
+        let outcome = match wait.completed_at {
+            Some(completed) => format!("done at {}", format_time(completed)),
+            None => "pending".to_string(),
+        };
+        entries.push(format!(
+            "{}  wait {}{} until {}, {}",
+            format_time(wait.started_at),
+            format_duration(wait.duration),
+            label,
+            format_time(wait.deadline),
+            outcome
+        ));
+    }
+    entries.extend(changes.map(|change| format_change(change.from, change.to, change.set)));
+
+    let mut out = format!("timeline (clock now at {}):", format_time(control.now()));
+    if entries.is_empty() {
+        out.push_str("\n  (no waits or clock changes)");
+    }
+    let skipped = entries.len().saturating_sub(TIMELINE_LIMIT);
+    if skipped > 0 {
+        let _ = write!(out, "\n  ... {} earlier entries", skipped);
+    }
+    for entry in &entries[skipped..] {
+        let _ = write!(out, "\n  {}", entry);
+    }
+    out
+}
+
+fn format_change(from: DateTime<Utc>, to: DateTime<Utc>, set: bool) -> String {
+    if set {
+        let direction = if to < from { ", backwards" } else { "" };
+        format!("{}  set to {}{}", format_time(from), format_time(to), direction)
+    } else {
+        format!("{}  advance {} to {}", format_time(from), format_duration(to - from), format_time(to))
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Format as `1d2h30m`, `45s` or `250ms`
+fn format_duration(duration: Duration) -> String {
+    if duration.is_zero() {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    if duration < Duration::zero() {
+        out.push('-');
+    }
+    let mut rest = duration.abs();
+    for (unit, suffix) in [
+        (Duration::days(1), "d"),
+        (Duration::hours(1), "h"),
+        (Duration::minutes(1), "m"),
+        (Duration::seconds(1), "s"),
+        (Duration::milliseconds(1), "ms"),
+    ] {
+        let count = rest.num_milliseconds() / unit.num_milliseconds();
+        if count > 0 {
+            let _ = write!(out, "{}{}", count, suffix);
+            rest -= unit * count as i32;
+        }
+    }
+    if out.is_empty() || out == "-" {
+        out.push_str("<1ms");
+    }
+    out
+}
+
+/// Assert on the waits made through a [`TimeControl`]'s provider
+///
+/// Takes an optional label, then `times = n` and/or `total = duration`:
+///
+/// ```rust,ignore
+/// assert_waited!(control, "accrual", times = 3, total = hours(3));
+/// assert_waited!(control, "billing");
+/// assert_waited!(control, total = days(30));
+/// ```
+#[macro_export]
+macro_rules! assert_waited {
+    ($control:expr, times = $times:expr, total = $total:expr $(,)?) => {
+        $crate::__assert_waited!($control, None, Some($times), Some($total))
+    };
+    ($control:expr, times = $times:expr $(,)?) => {
+        $crate::__assert_waited!($control, None, Some($times), None)
+    };
+    ($control:expr, total = $total:expr $(,)?) => {
+        $crate::__assert_waited!($control, None, None, Some($total))
+    };
+    ($control:expr, $label:expr, times = $times:expr, total = $total:expr $(,)?) => {
+        $crate::__assert_waited!($control, Some($label), Some($times), Some($total))
+    };
+    ($control:expr, $label:expr, times = $times:expr $(,)?) => {
+        $crate::__assert_waited!($control, Some($label), Some($times), None)
+    };
+    ($control:expr, $label:expr, total = $total:expr $(,)?) => {
+        $crate::__assert_waited!($control, Some($label), None, Some($total))
+    };
+    ($control:expr, $label:expr $(,)?) => {
+        $crate::__assert_waited!($control, Some($label), None, None)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_waited {
+    ($control:expr, $label:expr, $times:expr, $total:expr) => {
+        if let Err(message) = $crate::assert::check_waited(&$control, $label, $times, $total) {
+            panic!("assert_waited! failed: {}", message);
+        }
+    };
+}
+
+/// Assert that no timer is parked on a [`TimeControl`]'s clock
+#[macro_export]
+macro_rules! assert_no_pending_timers {
+    ($control:expr $(,)?) => {
+        if let Err(message) = $crate::assert::check_no_pending_timers(&$control) {
+            panic!("assert_no_pending_timers! failed: {}", message);
+        }
+    };
+}
+
+/// Assert that a [`SafeTimeProvider`] reads the given time
+///
+/// The expected time is a `DateTime<Utc>` or an RFC 3339 string.
+#[macro_export]
+macro_rules! assert_now {
+    ($provider:expr, $expected:expr $(,)?) => {
+        if let Err(message) = $crate::assert::check_now(&$provider, $expected) {
+            panic!("assert_now! failed: {}", message);
+        }
+    };
+}
+
+pub use crate::{assert_no_pending_timers, assert_now, assert_waited};
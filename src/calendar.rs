@@ -0,0 +1,449 @@
+//! Calendar-aware date arithmetic
+//!
+//! A month is not a fixed [`Duration`](chrono::Duration), so adding one to
+//! January 31st needs a rule for what happens when the target month is
+//! shorter. [`EndOfMonth`] names the common conventions; [`add_months`] and
+//! [`add_years`] use [`EndOfMonth::Clamp`].
+//!
+//! [`DayCount`] turns a pair of dates into the year fraction used for
+//! interest accrual.
+//!
+//! Business days are defined by a [`BusinessCalendar`]. [`HolidayCalendar`]
+//! combines a [`Weekend`] rule with a list of holidays, which can be loaded
+//! from a plain text or ICS file.
+//!
+//! ```
+//! use hourglass_rs::calendar::{add_months, add_months_with, EndOfMonth};
+//! use chrono::{DateTime, Utc};
+//!
+//! let jan31: DateTime<Utc> = "2023-01-31T12:00:00Z".parse().unwrap();
+//! assert_eq!(add_months(jan31, 1), "2023-02-28T12:00:00Z".parse::<DateTime<Utc>>().unwrap());
+//! assert_eq!(
+//!     add_months_with(jan31, 1, EndOfMonth::RollForward),
+//!     "2023-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+//! );
+//! ```
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What to do when the same day does not exist in the target month
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EndOfMonth {
+    /// Use the last day of the target month (Jan 31 + 1 month = Feb 28)
+    #[default]
+    Clamp,
+    /// Like `Clamp`, but a date on the last day of its month stays on the
+    /// last day (Feb 28 + 1 month = Mar 31)
+    PreserveEndOfMonth,
+    /// Use the first day of the month after the target (Jan 31 + 1 month = Mar 1)
+    RollForward,
+}
+
+/// Add calendar months, clamping to the end of shorter months
+pub fn add_months(date: DateTime<Utc>, months: i32) -> DateTime<Utc> {
+    add_months_with(date, months, EndOfMonth::Clamp)
+}
+
+/// Add calendar months using the given end-of-month convention
+///
+/// The time of day is kept. Negative `months` move backwards.
+pub fn add_months_with(date: DateTime<Utc>, months: i32, convention: EndOfMonth) -> DateTime<Utc> {
+    let naive = date.naive_utc();
+    let total = naive.year() * 12 + naive.month0() as i32 + months;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    let last = days_in_month(year, month);
+
+    let day = naive.day();
+    let target = if convention == EndOfMonth::PreserveEndOfMonth && is_last_day_of_month(naive.date()) {
+        ymd(year, month, last)
+    } else if day <= last {
+        ymd(year, month, day)
+    } else if convention == EndOfMonth::RollForward {
+        ymd(year, month, last).succ_opt().expect("date out of range")
+    } else {
+        ymd(year, month, last)
+    };
+    target.and_time(naive.time()).and_utc()
+}
+
+/// Add calendar years, clamping Feb 29 to Feb 28 in non-leap years
+pub fn add_years(date: DateTime<Utc>, years: i32) -> DateTime<Utc> {
+    add_years_with(date, years, EndOfMonth::Clamp)
+}
+
+/// Add calendar years using the given end-of-month convention
+pub fn add_years_with(date: DateTime<Utc>, years: i32, convention: EndOfMonth) -> DateTime<Utc> {
+    add_months_with(date, years * 12, convention)
+}
+
+/// Number of days in `month` (1-12) of `year`
+///
+/// # Panics
+///
+/// Panics if `month` is not between 1 and 12.
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => panic!("invalid month {month}"),
+    }
+}
+
+/// Whether `year` is a leap year in the proleptic Gregorian calendar
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Whether `date` falls on the last day of its month
+pub fn is_last_day_of_month(date: NaiveDate) -> bool {
+    date.day() == days_in_month(date.year(), date.month())
+}
+
+/// Last day of the month containing `date`
+pub fn end_of_month(date: NaiveDate) -> NaiveDate {
+    ymd(date.year(), date.month(), days_in_month(date.year(), date.month()))
+}
+
+/// Day-count convention for turning a period into a fraction of a year
+///
+/// Only the calendar dates of `start` and `end` are used; the time of day is
+/// ignored. A period that runs backwards gives a negative fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DayCount {
+    /// Actual days over 365, leap years included
+    #[default]
+    Actual365Fixed,
+    /// Actual days over 360
+    Actual360,
+    /// Actual days, split by calendar year over 365 or 366
+    ActualActualIsda,
+    /// 30/360 US (NASD), with the end-of-February adjustments
+    Thirty360Us,
+    /// 30E/360 (Eurobond basis)
+    ThirtyE360,
+}
+
+impl DayCount {
+    /// Fraction of a year between `start` and `end`
+    pub fn year_fraction(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+        let (start, end) = (start.date_naive(), end.date_naive());
+        if end < start {
+            return -self.year_fraction_between(end, start);
+        }
+        self.year_fraction_between(start, end)
+    }
+
+    /// Days between `start` and `end` as counted by this convention
+    pub fn day_count(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+        let (start, end) = (start.date_naive(), end.date_naive());
+        match self {
+            DayCount::Thirty360Us | DayCount::ThirtyE360 if end < start => -self.thirty_360_days(end, start),
+            DayCount::Thirty360Us | DayCount::ThirtyE360 => self.thirty_360_days(start, end),
+            _ => (end - start).num_days(),
+        }
+    }
+
+    fn year_fraction_between(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+        match self {
+            DayCount::Actual365Fixed => (end - start).num_days() as f64 / 365.0,
+            DayCount::Actual360 => (end - start).num_days() as f64 / 360.0,
+            DayCount::ActualActualIsda => {
+                let mut fraction = 0.0;
+                let mut from = start;
+                while from < end {
+                    let next_year = ymd(from.year() + 1, 1, 1);
+                    let to = next_year.min(end);
+                    let year_days = if is_leap_year(from.year()) { 366.0 } else { 365.0 };
+                    fraction += (to - from).num_days() as f64 / year_days;
+                    from = to;
+                }
+                fraction
+            }
+            DayCount::Thirty360Us | DayCount::ThirtyE360 => self.thirty_360_days(start, end) as f64 / 360.0,
+        }
+    }
+
+    fn thirty_360_days(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        let (mut d1, mut d2) = (start.day(), end.day());
+        if *self == DayCount::Thirty360Us {
+            let start_feb_end = start.month() == 2 && is_last_day_of_month(start);
+            let end_feb_end = end.month() == 2 && is_last_day_of_month(end);
+            if start_feb_end && end_feb_end {
+                d2 = 30;
+            }
+            if start_feb_end {
+                d1 = 30;
+            }
+            if d2 == 31 && d1 >= 30 {
+                d2 = 30;
+            }
+            d1 = d1.min(30);
+        } else {
+            d1 = d1.min(30);
+            d2 = d2.min(30);
+        }
+
+        360 * (end.year() - start.year()) as i64
+            + 30 * (end.month() as i64 - start.month() as i64)
+            + (d2 as i64 - d1 as i64)
+    }
+}
+
+/// A run of non-business days longer than this means the calendar has none
+const MAX_NON_BUSINESS_DAYS: u32 = 366;
+
+/// Decides which days are working days
+///
+/// Only [`is_business_day`](BusinessCalendar::is_business_day) is required;
+/// the other methods keep the time of day of their input.
+pub trait BusinessCalendar {
+    /// Whether `date` is a working day
+    fn is_business_day(&self, date: NaiveDate) -> bool;
+
+    /// First business day strictly after `date`
+    ///
+    /// # Panics
+    ///
+    /// Panics if no business day falls within a year after `date`.
+    fn next_business_day(&self, date: DateTime<Utc>) -> DateTime<Utc> {
+        step_business_day(self, date, 1)
+    }
+
+    /// Last business day strictly before `date`
+    ///
+    /// # Panics
+    ///
+    /// Panics if no business day falls within a year before `date`.
+    fn previous_business_day(&self, date: DateTime<Utc>) -> DateTime<Utc> {
+        step_business_day(self, date, -1)
+    }
+
+    /// `date` itself if it is a business day, otherwise the next one
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`next_business_day`](Self::next_business_day).
+    fn roll_forward(&self, date: DateTime<Utc>) -> DateTime<Utc> {
+        if self.is_business_day(date.date_naive()) {
+            date
+        } else {
+            self.next_business_day(date)
+        }
+    }
+
+    /// Move `days` business days forwards, or backwards if negative
+    ///
+    /// Counting starts from `date` whether or not it is a business day, so
+    /// one business day after a Saturday is the following Monday.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a year passes between two business days on the way.
+    fn add_business_days(&self, date: DateTime<Utc>, days: i32) -> DateTime<Utc> {
+        let direction = days.signum();
+        (0..days.unsigned_abs()).fold(date, |date, _| step_business_day(self, date, direction))
+    }
+}
+
+/// Step one business day in `direction`, giving up after a year of non-business days
+fn step_business_day<C: BusinessCalendar + ?Sized>(calendar: &C, date: DateTime<Utc>, direction: i32) -> DateTime<Utc> {
+    let step = chrono::Duration::days(direction as i64);
+    let mut date = date;
+    for _ in 0..MAX_NON_BUSINESS_DAYS {
+        date += step;
+        if calendar.is_business_day(date.date_naive()) {
+            return date;
+        }
+    }
+    panic!("no business day within {MAX_NON_BUSINESS_DAYS} days of {date}");
+}
+
+/// The days of the week that are not worked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weekend(u8);
+
+impl Weekend {
+    /// Saturday and Sunday
+    pub const SATURDAY_SUNDAY: Weekend = Weekend(1 << 5 | 1 << 6);
+    /// Friday and Saturday
+    pub const FRIDAY_SATURDAY: Weekend = Weekend(1 << 4 | 1 << 5);
+    /// Sunday only
+    pub const SUNDAY: Weekend = Weekend(1 << 6);
+    /// Every day is a working day
+    pub const NONE: Weekend = Weekend(0);
+
+    /// A weekend made of the given days
+    pub fn new(days: &[Weekday]) -> Self {
+        Weekend(days.iter().fold(0, |mask, day| mask | 1 << day.num_days_from_monday()))
+    }
+
+    /// Whether `day` is part of the weekend
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0 & 1 << day.num_days_from_monday() != 0
+    }
+}
+
+impl Default for Weekend {
+    fn default() -> Self {
+        Weekend::SATURDAY_SUNDAY
+    }
+}
+
+impl BusinessCalendar for Weekend {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.contains(date.weekday())
+    }
+}
+
+/// Error returned when a holiday list cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHolidaysError {
+    line: usize,
+    message: String,
+}
+
+impl ParseHolidaysError {
+    /// The 1-based line the error was found on
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl std::fmt::Display for ParseHolidaysError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid holiday list at line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseHolidaysError {}
+
+/// A business calendar made of a weekend rule and a set of holidays
+///
+/// Holiday lists are parsed from text with one date per line, optionally
+/// followed by a name. Blank lines and lines starting with `#` are ignored.
+/// ICS files are accepted too: the `DTSTART` of every event is a holiday and
+/// the other properties are skipped.
+///
+/// ```text
+/// # UK bank holidays
+/// 2024-12-25 Christmas Day
+/// 2024-12-26 Boxing Day
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    weekend: Weekend,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    /// Create a calendar with the given weekend and no holidays
+    pub fn new(weekend: Weekend) -> Self {
+        Self {
+            weekend,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// Parse a holiday list, using a Saturday/Sunday weekend
+    pub fn parse(source: &str) -> Result<Self, ParseHolidaysError> {
+        let mut calendar = Self::default();
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: String| ParseHolidaysError { line: index + 1, message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((property, value)) = line.split_once(':').filter(|(property, _)| is_ics_property(property)) {
+                if property.split(';').next() == Some("DTSTART") {
+                    let digits = value.get(..8).unwrap_or(value);
+                    let date = NaiveDate::parse_from_str(digits, "%Y%m%d")
+                        .map_err(|_| error(format!("invalid DTSTART date '{}'", value)))?;
+                    calendar.holidays.insert(date);
+                }
+                continue;
+            }
+
+            let date = line.split_whitespace().next().unwrap_or_default();
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| error(format!("expected a YYYY-MM-DD date, found '{}'", date)))?;
+            calendar.holidays.insert(date);
+        }
+        Ok(calendar)
+    }
+
+    /// Load a holiday list from a file
+    ///
+    /// Parse errors are reported as [`std::io::ErrorKind::InvalidData`].
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Replace the weekend rule
+    pub fn with_weekend(mut self, weekend: Weekend) -> Self {
+        self.weekend = weekend;
+        self
+    }
+
+    /// Add a holiday
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.add_holiday(date);
+        self
+    }
+
+    /// Add a holiday
+    pub fn add_holiday(&mut self, date: NaiveDate) {
+        self.holidays.insert(date);
+    }
+
+    /// Whether `date` is listed as a holiday
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    /// The weekend rule
+    pub fn weekend(&self) -> Weekend {
+        self.weekend
+    }
+
+    /// The holidays in date order
+    pub fn holidays(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.holidays.iter().copied()
+    }
+}
+
+impl BusinessCalendar for HolidayCalendar {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.weekend.is_business_day(date) && !self.is_holiday(date)
+    }
+}
+
+impl FromStr for HolidayCalendar {
+    type Err = ParseHolidaysError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HolidayCalendar::parse(s)
+    }
+}
+
+impl<C: BusinessCalendar + ?Sized> BusinessCalendar for &C {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        (**self).is_business_day(date)
+    }
+}
+
+/// ICS property names are upper case, with parameters after `;`
+fn is_ics_property(property: &str) -> bool {
+    let name = property.split(';').next().unwrap_or_default();
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c == '-')
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("date out of range")
+}
@@ -1,24 +1,54 @@
+use crate::offset::{self, OffsetTimeProvider};
 use crate::provider::SharedTimeProvider;
+use crate::record::RecordingTimeProvider;
+use crate::replay::{DivergencePolicy, ReplayTimeProvider};
+use crate::scaled::ScaledTimeProvider;
 use crate::system::SystemTimeProvider;
 use crate::test::TestTimeProvider;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// Time source configuration for different environments
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum TimeSource {
     /// Use system time (production)
+    #[default]
     System,
     /// Use test time with initial timestamp
     Test(DateTime<Utc>),
     /// Use test time starting at current system time
     TestNow,
+    /// Use system time and record every read and wait to a trace file
+    Record(PathBuf),
+    /// Use test time played back from a recorded trace file
+    Replay {
+        /// The trace to replay
+        path: PathBuf,
+        /// What to do when the code stops matching the trace
+        on_divergence: DivergencePolicy,
+    },
+    /// Use a clock that starts at `start` and runs `factor` times faster than real time
+    Scaled {
+        /// The time the clock starts at
+        start: DateTime<Utc>,
+        /// How many times faster than real time the clock runs
+        factor: f64,
+    },
+    /// Use system time shifted by a fixed offset
+    Offset(Duration),
+    /// Use system time shifted so that it reads the given time when the provider is created
+    Anchored(DateTime<Utc>),
 }
 
 impl TimeSource {
     /// Create from environment variables
-    /// - TIME_SOURCE: "system" (default) or "test"
-    /// - TIME_START: RFC3339 timestamp for test mode start time
+    /// - TIME_SOURCE: "system" (default), "test", "record", "replay", "scaled", "offset" or "anchored"
+    /// - TIME_START: RFC3339 timestamp for test, scaled and anchored mode start time
+    /// - TIME_SCALE: speed-up factor for scaled mode
+    /// - TIME_OFFSET: ISO 8601 duration such as "-P90D" for offset mode
+    /// - TIME_TRACE: trace file path for record and replay modes
+    /// - TIME_DIVERGENCE: "error" (default), "fallback" or "skip" for replay mode
     pub fn from_env() -> Self {
         match std::env::var("TIME_SOURCE").as_deref() {
             Ok("test") => {
@@ -33,22 +63,89 @@ impl TimeSource {
                     TimeSource::TestNow
                 }
             }
+            Ok("record") => match std::env::var("TIME_TRACE") {
+                Ok(path) => TimeSource::Record(PathBuf::from(path)),
+                Err(_) => {
+                    eprintln!("TIME_TRACE not set, using system time without recording");
+                    TimeSource::System
+                }
+            },
+            Ok("replay") => {
+                let on_divergence = match std::env::var("TIME_DIVERGENCE").as_deref() {
+                    Ok("fallback") => DivergencePolicy::FallBackToTestClock,
+                    Ok("skip") => DivergencePolicy::Skip,
+                    Ok("error") | Err(_) => DivergencePolicy::Error,
+                    Ok(other) => {
+                        eprintln!("Invalid TIME_DIVERGENCE '{}', using error", other);
+                        DivergencePolicy::Error
+                    }
+                };
+                match std::env::var("TIME_TRACE") {
+                    Ok(path) => TimeSource::Replay {
+                        path: PathBuf::from(path),
+                        on_divergence,
+                    },
+                    Err(_) => {
+                        eprintln!("TIME_TRACE not set, using current time");
+                        TimeSource::TestNow
+                    }
+                }
+            }
+            Ok("scaled") => {
+                let start = std::env::var("TIME_START")
+                    .ok()
+                    .and_then(|start| DateTime::parse_from_rfc3339(&start).ok())
+                    .map_or_else(Utc::now, |start| start.with_timezone(&Utc));
+                let factor = match std::env::var("TIME_SCALE").map(|scale| scale.parse::<f64>()) {
+                    Ok(Ok(factor)) if factor.is_finite() && factor > 0.0 => factor,
+                    _ => {
+                        eprintln!("Invalid or missing TIME_SCALE, using real speed");
+                        1.0
+                    }
+                };
+                TimeSource::Scaled { start, factor }
+            }
+            Ok("offset") => match std::env::var("TIME_OFFSET").map(|offset| offset::parse_iso8601_duration(&offset)) {
+                Ok(Some(offset)) => TimeSource::Offset(offset),
+                _ => {
+                    eprintln!("Invalid or missing TIME_OFFSET, using system time");
+                    TimeSource::System
+                }
+            },
+            Ok("anchored") => match std::env::var("TIME_START").map(|start| DateTime::parse_from_rfc3339(&start)) {
+                Ok(Ok(start)) => TimeSource::Anchored(start.with_timezone(&Utc)),
+                _ => {
+                    eprintln!("Invalid or missing TIME_START, using system time");
+                    TimeSource::System
+                }
+            },
             _ => TimeSource::System,
         }
     }
     
     /// Convert to a time provider instance
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the trace file for [`TimeSource::Record`] cannot be created,
+    /// the one for [`TimeSource::Replay`] cannot be read, or the factor of
+    /// [`TimeSource::Scaled`] is not positive.
     pub fn into_provider(self) -> SharedTimeProvider {
         match self {
             TimeSource::System => Arc::new(SystemTimeProvider),
             TimeSource::Test(start) => Arc::new(TestTimeProvider::new(start)),
             TimeSource::TestNow => Arc::new(TestTimeProvider::new_at_now()),
+            TimeSource::Record(path) => match RecordingTimeProvider::create(&path) {
+                Ok(recorder) => Arc::new(recorder),
+                Err(e) => panic!("failed to create time trace {}: {}", path.display(), e),
+            },
+            TimeSource::Replay { path, on_divergence } => match ReplayTimeProvider::load(&path, on_divergence) {
+                Ok(replay) => Arc::new(replay),
+                Err(e) => panic!("failed to read time trace {}: {}", path.display(), e),
+            },
+            TimeSource::Scaled { start, factor } => Arc::new(ScaledTimeProvider::new(start, factor)),
+            TimeSource::Offset(offset) => Arc::new(OffsetTimeProvider::new(offset)),
+            TimeSource::Anchored(at) => Arc::new(OffsetTimeProvider::anchored(at)),
         }
     }
-}
-
-impl Default for TimeSource {
-    fn default() -> Self {
-        TimeSource::System
-    }
 }
\ No newline at end of file
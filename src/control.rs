@@ -1,4 +1,9 @@
-use crate::test::TestTimeProvider;
+use crate::calendar::{self, BusinessCalendar};
+use crate::provider::TimeProvider;
+use crate::event::ClockEvent;
+use crate::node::ClockNode;
+use crate::runtime;
+use crate::test::{ClockChange, ClockSnapshot, ObserverId, PendingTimer, TestTimeProvider, WaitMode, WaitRecord, IDLE_POLLS};
 use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
 
@@ -18,17 +23,167 @@ impl TimeControl {
         self.provider.advance(duration);
     }
     
+    /// Advance time to the same day `months` calendar months later
+    /// 
+    /// Days missing from the target month are clamped to its last day, so
+    /// January 31st advances to the end of February.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `months` does not fit in an `i32`.
+    pub fn advance_months(&self, months: u32) {
+        let months = i32::try_from(months).expect("cannot advance more than i32::MAX months");
+        let now = self.provider.now();
+        let target = calendar::add_months(now, months);
+        self.provider.advance(target - now);
+    }
+    
+    /// Advance time by `days` business days of `calendar`
+    /// 
+    /// Weekends and holidays are skipped and the time of day is kept, so
+    /// advancing one business day from Friday lands on Monday.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `days` does not fit in an `i32`, or if a year passes
+    /// between two business days of `calendar`.
+    pub fn advance_business_days(&self, days: u32, calendar: &impl BusinessCalendar) {
+        let days = i32::try_from(days).expect("cannot advance more than i32::MAX business days");
+        let now = self.provider.now();
+        let target = calendar.add_business_days(now, days);
+        self.provider.advance(target - now);
+    }
+    
+    /// Advance time to the earliest parked deadline, waking every sleeper due then
+    /// 
+    /// Returns the deadline, or `None` without moving the clock if nothing
+    /// is parked.
+    pub fn advance_to_next_timer(&self) -> Option<DateTime<Utc>> {
+        let deadline = self.provider.next_deadline()?;
+        let now = self.provider.now();
+        self.provider.advance((deadline - now).max(Duration::zero()));
+        Some(deadline)
+    }
+    
+    /// Fire every timer up to `deadline` in order, then advance to `deadline`
+    /// 
+    /// The clock steps from one parked deadline to the next, letting woken
+    /// tasks run and park again in between, so a loop that sleeps repeatedly
+    /// sees each of its deadlines. Meant for [`WaitMode::Parked`].
+    pub async fn run_until(&self, deadline: DateTime<Utc>) {
+        self.step_until(deadline).await;
+        let now = self.provider.now();
+        if deadline > now {
+            self.provider.advance(deadline - now);
+            self.settle().await;
+        }
+    }
+    
+    /// Fire timers in order until none remain within `bound` of the current time
+    /// 
+    /// Unlike [`run_until`](Self::run_until), the clock is left at the last
+    /// deadline that fired.
+    pub async fn run_until_idle(&self, bound: Duration) {
+        self.step_until(self.provider.now() + bound).await;
+    }
+    
+    async fn step_until(&self, limit: DateTime<Utc>) {
+        loop {
+            self.settle().await;
+            match self.provider.next_deadline() {
+                Some(next) if next <= limit => {
+                    self.advance_to_next_timer();
+                }
+                _ => break,
+            }
+        }
+    }
+    
+    /// Let woken tasks run until none of them has started a wait or timer for a while
+    async fn settle(&self) {
+        let mut seen = self.provider.activity();
+        let mut idle_polls = 0;
+        while idle_polls < IDLE_POLLS {
+            runtime::yield_now().await;
+            let activity = self.provider.activity();
+            if activity == seen {
+                idle_polls += 1;
+            } else {
+                seen = activity;
+                idle_polls = 0;
+            }
+        }
+    }
+    
     /// Set time to a specific value
+    ///
+    /// Only the wall clock moves; [`instant`](crate::SafeTimeProvider::instant)
+    /// does not, so use [`advance`](Self::advance) when waits released by
+    /// the move should count as elapsed time.
     pub fn set(&self, time: DateTime<Utc>) {
         self.provider.set(time);
     }
     
+    /// Call `callback` whenever this control moves the clock
+    /// 
+    /// See [`TestTimeProvider::on_clock_change`].
+    pub fn on_clock_change(&self, callback: impl Fn(&ClockEvent) + Send + Sync + 'static) -> ObserverId {
+        self.provider.on_clock_change(callback)
+    }
+    
+    /// Stop calling a callback registered with [`on_clock_change`](Self::on_clock_change)
+    pub fn remove_observer(&self, id: ObserverId) -> bool {
+        self.provider.remove_observer(id)
+    }
+    
+    /// Create a simulated machine whose clock follows this one with its own offset and drift
+    /// 
+    /// Advancing or setting this clock moves every node; use
+    /// [`ClockNode::skew`] and [`ClockNode::set_drift_ppm`] to make one node
+    /// disagree with the others. Waits on a node are counted here.
+    pub fn fork_node(&self, name: &str) -> ClockNode {
+        ClockNode::new(name, self.provider.clone())
+    }
+    
+    /// Save the clock time, wait statistics, wait history and pending sleepers
+    /// 
+    /// Use with [`restore`](Self::restore) to branch a scenario: run one
+    /// path, rewind, then run another from the same point.
+    pub fn snapshot(&self) -> ClockSnapshot {
+        self.provider.snapshot()
+    }
+    
+    /// Put the clock time, wait statistics and wait history back to `snapshot`
+    /// 
+    /// Sleepers are not cancelled or re-armed; see
+    /// [`TestTimeProvider::restore`] for how they behave.
+    pub fn restore(&self, snapshot: &ClockSnapshot) {
+        self.provider.restore(snapshot);
+    }
+    
+    /// Get the current wait mode
+    pub fn wait_mode(&self) -> WaitMode {
+        self.provider.wait_mode()
+    }
+    
+    /// Change how subsequent waits interact with the clock
+    /// 
+    /// In [`WaitMode::Parked`], waits block until this control moves the
+    /// clock past their deadline, which lets background loops be stepped
+    /// deterministically instead of racing ahead. [`WaitMode::AutoAdvance`]
+    /// parks the same way but jumps to the earliest deadline on its own once
+    /// no task has used the clock for a few scheduler rounds; it cannot see
+    /// tasks blocked on anything but the clock.
+    pub fn set_wait_mode(&self, mode: WaitMode) {
+        self.provider.set_wait_mode(mode);
+    }
+    
     /// Get the total duration waited since creation or last reset
     pub fn total_waited(&self) -> Duration {
         self.provider.total_waited()
     }
     
-    /// Reset wait tracking statistics
+    /// Reset wait tracking statistics, the wait history and the timeline of clock changes
     pub fn reset_wait_tracking(&self) {
         self.provider.reset_wait_tracking();
     }
@@ -37,6 +192,54 @@ impl TimeControl {
     pub fn wait_call_count(&self) -> usize {
         self.provider.wait_call_count()
     }
+    
+    /// Get every wait since creation or last reset, in the order they started
+    /// 
+    /// Only the most recent waits are kept; see [`set_wait_history_limit`](Self::set_wait_history_limit).
+    pub fn wait_history(&self) -> Vec<WaitRecord> {
+        self.provider.wait_history()
+    }
+    
+    /// Keep at most `limit` waits, and as many clock changes, dropping the oldest first
+    /// 
+    /// Defaults to [`DEFAULT_WAIT_HISTORY_LIMIT`](crate::test::DEFAULT_WAIT_HISTORY_LIMIT).
+    /// The wait statistics keep counting every wait.
+    pub fn set_wait_history_limit(&self, limit: usize) {
+        self.provider.set_wait_history_limit(limit);
+    }
+    
+    /// Get the waits tagged with `label` since creation or last reset
+    pub fn waits_for(&self, label: &str) -> Vec<WaitRecord> {
+        let mut waits = self.provider.wait_history();
+        waits.retain(|wait| wait.label.as_deref() == Some(label));
+        waits
+    }
+    
+    /// Get the sleepers parked on the clock, earliest deadline first
+    /// 
+    /// Only waits that are blocked on the clock show up here, so in
+    /// [`WaitMode::Immediate`] this is usually empty apart from timeouts.
+    pub fn pending_timers(&self) -> Vec<PendingTimer> {
+        self.provider.pending_timers()
+    }
+    
+    /// Get the earliest deadline of a parked sleeper
+    pub fn next_deadline(&self) -> Option<DateTime<Utc>> {
+        self.provider.next_deadline()
+    }
+    
+    /// Get the number of sleepers parked on the clock
+    pub fn pending_count(&self) -> usize {
+        self.provider.pending_count()
+    }
+    
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.provider.now()
+    }
+    
+    pub(crate) fn clock_changes(&self) -> Vec<ClockChange> {
+        self.provider.clock_changes()
+    }
 }
 
 impl std::fmt::Debug for TimeControl {
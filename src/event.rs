@@ -0,0 +1,147 @@
+use crate::test::{ObserverId, TestTimeProvider};
+use chrono::{DateTime, Utc};
+use futures_core::Stream;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// A move of the test clock made through `advance` or `set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockEvent {
+    /// The clock was advanced
+    Advanced {
+        /// Time before the move
+        from: DateTime<Utc>,
+        /// Time after the move
+        to: DateTime<Utc>,
+    },
+    /// The clock was set to the same or a later time
+    Set {
+        /// Time before the move
+        from: DateTime<Utc>,
+        /// Time after the move
+        to: DateTime<Utc>,
+    },
+    /// The clock was set or advanced to an earlier time
+    WentBackwards {
+        /// Time before the move
+        from: DateTime<Utc>,
+        /// Time after the move
+        to: DateTime<Utc>,
+    },
+}
+
+impl ClockEvent {
+    /// Time before the move
+    pub fn from(&self) -> DateTime<Utc> {
+        match self {
+            ClockEvent::Advanced { from, .. }
+            | ClockEvent::Set { from, .. }
+            | ClockEvent::WentBackwards { from, .. } => *from,
+        }
+    }
+
+    /// Time after the move
+    pub fn to(&self) -> DateTime<Utc> {
+        match self {
+            ClockEvent::Advanced { to, .. }
+            | ClockEvent::Set { to, .. }
+            | ClockEvent::WentBackwards { to, .. } => *to,
+        }
+    }
+}
+
+/// Clock changes a [`ClockEvents`] keeps while they are not read; older
+/// ones are dropped to make room
+pub const CLOCK_EVENT_CAPACITY: usize = 1024;
+
+/// Stream of clock changes returned by
+/// [`SafeTimeProvider::subscribe`](crate::SafeTimeProvider::subscribe)
+///
+/// Events are buffered from the moment of subscription until they are read,
+/// up to [`CLOCK_EVENT_CAPACITY`], after which the oldest are dropped.
+/// Outside test mode the clock is never moved by hand, so the stream never
+/// yields.
+pub struct ClockEvents {
+    queue: Arc<Mutex<EventQueue>>,
+    subscription: Option<(Arc<TestTimeProvider>, ObserverId)>,
+}
+
+#[derive(Debug, Default)]
+struct EventQueue {
+    events: VecDeque<ClockEvent>,
+    waker: Option<Waker>,
+}
+
+impl ClockEvents {
+    pub(crate) fn new(provider: Option<Arc<TestTimeProvider>>) -> Self {
+        let queue = Arc::new(Mutex::new(EventQueue::default()));
+        let subscription = provider.map(|provider| {
+            let sink = queue.clone();
+            let id = provider.on_clock_change(move |event| {
+                let waker = {
+                    let mut queue = sink.lock();
+                    if queue.events.len() == CLOCK_EVENT_CAPACITY {
+                        queue.events.pop_front();
+                    }
+                    queue.events.push_back(*event);
+                    queue.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            });
+            (provider, id)
+        });
+        Self { queue, subscription }
+    }
+
+    /// Wait for the next clock change
+    pub async fn next_event(&mut self) -> ClockEvent {
+        poll_fn(|cx| self.poll_event(cx)).await
+    }
+
+    /// Take the next buffered clock change without waiting
+    pub fn try_next_event(&mut self) -> Option<ClockEvent> {
+        self.queue.lock().events.pop_front()
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<ClockEvent> {
+        let mut queue = self.queue.lock();
+        match queue.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Stream for ClockEvents {
+    type Item = ClockEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_event(cx).map(Some)
+    }
+}
+
+impl Drop for ClockEvents {
+    fn drop(&mut self) {
+        if let Some((provider, id)) = self.subscription.take() {
+            provider.remove_observer(id);
+        }
+    }
+}
+
+impl std::fmt::Debug for ClockEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClockEvents")
+            .field("buffered", &self.queue.lock().events.len())
+            .field("subscribed", &self.subscription.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,244 @@
+use crate::instant::Instant;
+use crate::provider::sealed::Internal;
+use crate::provider::{SharedTimeProvider, TimeProvider};
+use crate::sleep::{ClockMap, Sleep};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Faults to apply to a clock's `now()` readings
+///
+/// Built up with the `with_*` methods and applied with
+/// [`SafeTimeProvider::with_faults`](crate::SafeTimeProvider::with_faults):
+///
+/// ```
+/// use hourglass_rs::{ClockFaults, SafeTimeProvider, TimeSource};
+/// use chrono::Duration;
+///
+/// let time = SafeTimeProvider::new(TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap()));
+/// let ntp_corrected = time.with_faults(
+///     ClockFaults::new()
+///         .with_skew(Duration::seconds(2))
+///         .with_jump_at("2024-01-01T01:00:00Z".parse().unwrap(), Duration::seconds(-2)),
+/// );
+/// assert_eq!(ntp_corrected.now(), "2024-01-01T00:00:02Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClockFaults {
+    skew: Duration,
+    drift_ppm: f64,
+    jitter: Duration,
+    seed: u64,
+    jumps: Vec<(DateTime<Utc>, Duration)>,
+}
+
+impl ClockFaults {
+    /// No faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a constant offset to every reading
+    pub fn with_skew(mut self, skew: Duration) -> Self {
+        self.skew = skew;
+        self
+    }
+
+    /// Run fast (positive) or slow (negative) by `ppm` parts per million
+    ///
+    /// Drift accumulates from the moment the faults are applied.
+    pub fn with_drift_ppm(mut self, ppm: f64) -> Self {
+        self.drift_ppm = ppm;
+        self
+    }
+
+    /// Add uniformly distributed noise of up to `max` either way to each reading
+    ///
+    /// Bounds beyond about 292 years, the range of a nanosecond count, are
+    /// capped there.
+    pub fn with_jitter(mut self, max: Duration) -> Self {
+        self.jitter = max.abs();
+        self
+    }
+
+    /// Seed the jitter so a failing run can be reproduced
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Step the clock by `by` once the underlying clock reaches `at`
+    ///
+    /// Negative steps make `now()` go backwards, like an NTP correction.
+    pub fn with_jump_at(mut self, at: DateTime<Utc>, by: Duration) -> Self {
+        self.jumps.push((at, by));
+        self
+    }
+
+    pub(crate) fn drift_ppm(&self) -> f64 {
+        self.drift_ppm
+    }
+
+    /// Offset from the true time at `time`, excluding jitter
+    pub(crate) fn offset(&self, origin: DateTime<Utc>, time: DateTime<Utc>) -> Duration {
+        let jumps = self.jumps.iter().filter(|(at, _)| *at <= time).map(|(_, by)| *by);
+        self.skew + self.drift(time - origin) + jumps.fold(Duration::zero(), |total, by| total + by)
+    }
+
+    fn drift(&self, elapsed: Duration) -> Duration {
+        if self.drift_ppm == 0.0 {
+            return Duration::zero();
+        }
+        let nanos = elapsed.num_nanoseconds().unwrap_or(i64::MAX) as f64;
+        Duration::nanoseconds((nanos * self.drift_ppm / 1e6) as i64)
+    }
+
+    /// The true time at which the faulty clock reads `reading`, using the
+    /// jumps already applied at `now`
+    pub(crate) fn true_time(&self, origin: DateTime<Utc>, now: DateTime<Utc>, reading: DateTime<Utc>) -> DateTime<Utc> {
+        let jumps = self.jumps.iter().filter(|(at, _)| *at <= now).map(|(_, by)| *by);
+        let fixed = self.skew + jumps.fold(Duration::zero(), |total, by| total + by);
+        let elapsed = (reading - fixed - origin).num_nanoseconds().unwrap_or(i64::MAX) as f64;
+        origin + Duration::nanoseconds((elapsed / (1.0 + self.drift_ppm / 1e6)) as i64)
+    }
+}
+
+/// Time provider whose `now()` readings are distorted by [`ClockFaults`]
+///
+/// Waits for a duration take that long on the underlying clock, and
+/// deadlines are converted with the faults in effect when the wait starts,
+/// so a scheduled jump during a wait shows up when it returns. Sleeps report
+/// their deadline as read on this clock. The monotonic
+/// [`instant`](TimeProvider::instant) is left alone, as a real monotonic
+/// clock is not stepped.
+pub struct FaultyClock {
+    clock: Arc<FaultState>,
+    rng: Mutex<SplitMix64>,
+}
+
+/// Everything needed to convert between faulty readings and true time,
+/// shared with the sleeps the clock hands out
+struct FaultState {
+    inner: SharedTimeProvider,
+    faults: ClockFaults,
+    origin: DateTime<Utc>,
+}
+
+impl FaultyClock {
+    /// Wrap `inner`, starting drift from its current time
+    pub fn new(inner: SharedTimeProvider, faults: ClockFaults) -> Self {
+        Self {
+            rng: Mutex::new(SplitMix64(faults.seed)),
+            clock: Arc::new(FaultState {
+                origin: inner.now(),
+                inner,
+                faults,
+            }),
+        }
+    }
+
+    /// The faults being applied
+    pub fn faults(&self) -> &ClockFaults {
+        &self.clock.faults
+    }
+
+    /// Offset from the underlying clock right now, excluding jitter
+    pub fn offset(&self) -> Duration {
+        self.clock.faults.offset(self.clock.origin, self.clock.inner.now())
+    }
+
+    fn jitter(&self) -> Duration {
+        let max = self.clock.faults.jitter.num_nanoseconds().unwrap_or(i64::MAX);
+        if max == 0 {
+            return Duration::zero();
+        }
+        // Wide enough for any bound, which is at most i64::MAX nanoseconds
+        let span = max as i128 * 2 + 1;
+        let offset = self.rng.lock().next() as i128 % span - max as i128;
+        Duration::nanoseconds(offset as i64)
+    }
+
+    /// Report a sleep on the underlying clock in this clock's readings
+    fn map_sleep(&self, sleep: Sleep, deadline: DateTime<Utc>) -> Sleep {
+        sleep.mapped(deadline, self.clock.clone())
+    }
+}
+
+impl ClockMap for FaultState {
+    fn to_inner(&self, reading: DateTime<Utc>) -> DateTime<Utc> {
+        self.faults.true_time(self.origin, self.inner.now(), reading)
+    }
+}
+
+#[async_trait]
+impl TimeProvider for FaultyClock {
+    fn now(&self) -> DateTime<Utc> {
+        let clock = &self.clock;
+        let now = clock.inner.now();
+        now + clock.faults.offset(clock.origin, now) + self.jitter()
+    }
+
+    fn instant(&self) -> Instant {
+        self.clock.inner.instant()
+    }
+
+    async fn wait(&self, duration: Duration) {
+        self.clock.inner.wait(duration).await
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.clock.inner.wait_until(self.clock.to_inner(deadline)).await
+    }
+
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        self.clock.inner.wait_labeled(label, duration).await
+    }
+
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        self.clock.inner.wait_until_labeled(label, self.clock.to_inner(deadline)).await
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let sleep = self.clock.inner.sleep(duration);
+        let at = sleep.deadline();
+        self.map_sleep(sleep, at + self.clock.faults.offset(self.clock.origin, at))
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        self.map_sleep(self.clock.inner.sleep_until(self.clock.to_inner(deadline)), deadline)
+    }
+
+    fn deadline_timer(&self, deadline: DateTime<Utc>, internal: Internal) -> Sleep {
+        let timer = self.clock.inner.deadline_timer(self.clock.to_inner(deadline), internal);
+        self.map_sleep(timer, deadline)
+    }
+
+    fn is_test(&self) -> bool {
+        self.clock.inner.is_test()
+    }
+}
+
+impl std::fmt::Debug for FaultyClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultyClock")
+            .field("faults", &self.clock.faults)
+            .field("origin", &self.clock.origin)
+            .finish()
+    }
+}
+
+/// Small seeded generator for jitter, so runs are reproducible without a
+/// dependency on `rand`
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
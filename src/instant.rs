@@ -0,0 +1,93 @@
+use crate::safe::SafeTimeProvider;
+use chrono::Duration;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::sync::OnceLock;
+
+/// A point on a provider's monotonic clock
+///
+/// Unlike [`DateTime<Utc>`](chrono::DateTime), instants never move backwards,
+/// even when a test sets the wall clock to an earlier time. Instants are only
+/// comparable with other instants from the same provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(std::time::Duration);
+
+impl Instant {
+    /// Create an instant at the given offset from the clock's origin
+    pub(crate) fn from_origin(offset: std::time::Duration) -> Self {
+        Self(offset)
+    }
+
+    /// Read the process-wide monotonic system clock
+    pub(crate) fn system_now() -> Self {
+        static ORIGIN: OnceLock<std::time::Instant> = OnceLock::new();
+        let origin = *ORIGIN.get_or_init(std::time::Instant::now);
+        Self(origin.elapsed())
+    }
+
+    /// Time elapsed since `earlier`, or zero if `earlier` is later than this instant
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_else(Duration::zero)
+    }
+
+    /// Time elapsed since `earlier`, or `None` if `earlier` is later than this instant
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        let elapsed = self.0.checked_sub(earlier.0)?;
+        Duration::from_std(elapsed).ok()
+    }
+
+    /// Time elapsed since this instant according to `provider`
+    pub fn elapsed(&self, provider: &SafeTimeProvider) -> Duration {
+        provider.instant().duration_since(*self)
+    }
+
+    /// Add a duration, returning `None` on overflow or before the clock's origin
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        match duration.to_std() {
+            Ok(forward) => self.0.checked_add(forward).map(Instant),
+            Err(_) => self.0.checked_sub(duration.abs().to_std().ok()?).map(Instant),
+        }
+    }
+
+    /// Subtract a duration, returning `None` on overflow or before the clock's origin
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.checked_add(-duration)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::safe::SafeTimeProvider;
+use crate::sleep::Sleep;
+use chrono::{DateTime, Duration, Utc};
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// How an [`Interval`] catches up after ticks were missed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// Fire every missed tick back to back, keeping the original schedule
+    #[default]
+    Burst,
+    /// Restart the schedule one period after the late tick
+    Delay,
+    /// Drop missed ticks and resume on the next multiple of the period
+    Skip,
+}
+
+impl MissedTickBehavior {
+    /// Compute the next deadline after a tick scheduled for `timeout` fired at `now`
+    fn next_timeout(&self, timeout: DateTime<Utc>, now: DateTime<Utc>, period: Duration) -> DateTime<Utc> {
+        match self {
+            MissedTickBehavior::Burst => timeout + period,
+            MissedTickBehavior::Delay => now + period,
+            MissedTickBehavior::Skip => {
+                let behind = (now - timeout).num_nanoseconds().unwrap_or(i64::MAX);
+                let period_nanos = period.num_nanoseconds().unwrap_or(i64::MAX);
+                now + period - Duration::nanoseconds(behind % period_nanos)
+            }
+        }
+    }
+}
+
+/// A tick that fires this late on a real clock counts as missed; the test
+/// clock has no scheduling jitter, so there any lateness counts
+const MISSED_TICK_SLACK: Duration = Duration::milliseconds(5);
+
+/// Ticker returned by [`SafeTimeProvider::interval`] that fires once per period
+///
+/// Ticks are scheduled from the previous deadline rather than from when the
+/// work finished, so loops built on an interval do not drift. In test mode
+/// the ticks follow the virtual clock, so advancing an hourly interval by five
+/// hours yields the ticks that fell in between.
+pub struct Interval {
+    provider: SafeTimeProvider,
+    sleep: Pin<Box<Sleep>>,
+    period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+}
+
+impl Interval {
+    pub(crate) fn new(provider: SafeTimeProvider, start: DateTime<Utc>, period: Duration) -> Self {
+        assert!(period > Duration::zero(), "interval period must be positive");
+        let sleep = Box::pin(provider.sleep_until(start));
+        Self {
+            provider,
+            sleep,
+            period,
+            missed_tick_behavior: MissedTickBehavior::default(),
+        }
+    }
+
+    /// Wait for the next tick and return the time it was scheduled for
+    pub async fn tick(&mut self) -> DateTime<Utc> {
+        poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Poll for the next tick
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<DateTime<Utc>> {
+        if self.sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        let timeout = self.sleep.deadline();
+        let now = self.provider.now();
+        let slack = if self.provider.is_test_mode() {
+            Duration::zero()
+        } else {
+            MISSED_TICK_SLACK
+        };
+        let next = if now - timeout > slack {
+            self.missed_tick_behavior.next_timeout(timeout, now, self.period)
+        } else {
+            timeout + self.period
+        };
+        self.sleep.set(self.provider.sleep_until(next));
+        Poll::Ready(timeout)
+    }
+
+    /// Restart the schedule so the next tick fires one period from now
+    pub fn reset(&mut self) {
+        let next = self.provider.now() + self.period;
+        self.sleep.set(self.provider.sleep_until(next));
+    }
+
+    /// The period between ticks
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Get the missed tick behavior
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.missed_tick_behavior
+    }
+
+    /// Set the missed tick behavior
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.missed_tick_behavior = behavior;
+    }
+}
+
+impl std::fmt::Debug for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interval")
+            .field("next_tick", &self.sleep.deadline())
+            .field("period", &self.period)
+            .field("missed_tick_behavior", &self.missed_tick_behavior)
+            .finish()
+    }
+}
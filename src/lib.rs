@@ -7,7 +7,7 @@
 //! 
 //! - **Zero overhead** in production - thin wrapper around system time
 //! - **Time manipulation** in tests - advance time, set specific times
-//! - **Async support** - works seamlessly with tokio's async runtime
+//! - **Async support** - works with tokio, async-io runtimes or any executor
 //! - **Type safety** - can't accidentally manipulate time in production
 //! - **Test isolation** - each test gets its own time control
 //! 
@@ -55,20 +55,52 @@
 //! }
 //! ```
 
+pub mod assert;
+pub mod calendar;
 pub mod config;
 pub mod control;
+pub mod event;
+pub mod fault;
+pub mod instant;
+pub mod interval;
+pub mod node;
+pub mod offset;
 pub mod provider;
+pub mod record;
+pub mod replay;
+pub mod runtime;
 pub mod safe;
+pub mod scaled;
+pub mod schedule;
+pub mod sleep;
 pub mod system;
 pub mod test;
+pub mod timeout;
+pub mod trace;
+pub mod zoned;
 
 // Re-export main types for convenience
 pub use config::TimeSource;
 pub use control::TimeControl;
+pub use event::{ClockEvent, ClockEvents};
+pub use fault::{ClockFaults, FaultyClock};
+pub use instant::Instant;
+pub use interval::{Interval, MissedTickBehavior};
+pub use node::ClockNode;
+pub use offset::OffsetTimeProvider;
 pub use provider::{SharedTimeProvider, TimeProvider};
+pub use record::RecordingTimeProvider;
+pub use replay::{DivergencePolicy, ReplayTimeProvider};
+pub use runtime::TaskId;
 pub use safe::SafeTimeProvider;
+pub use scaled::{ScaleControl, ScaledTimeProvider};
+pub use schedule::Schedule;
+pub use sleep::Sleep;
 pub use system::SystemTimeProvider;
-pub use test::TestTimeProvider;
+pub use test::{ClockSnapshot, ObserverId, PendingTimer, TestTimeProvider, WaitMode, WaitRecord};
+pub use timeout::{Elapsed, Timeout};
+pub use zoned::ZonedProvider;
 
 // Re-export chrono types that are part of our API
-pub use chrono::{DateTime, Duration, Utc};
\ No newline at end of file
+pub use chrono::{DateTime, Duration, Utc};
+pub use chrono_tz::Tz;
\ No newline at end of file
@@ -0,0 +1,215 @@
+use crate::fault::ClockFaults;
+use crate::instant::Instant;
+use crate::provider::sealed::Internal;
+use crate::provider::TimeProvider;
+use crate::safe::SafeTimeProvider;
+use crate::sleep::{ClockMap, Sleep};
+use crate::test::TestTimeProvider;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// A simulated machine whose clock follows a parent test clock with its own
+/// offset and drift
+///
+/// Created with [`TimeControl::fork_node`](crate::TimeControl::fork_node).
+/// Every node moves when the parent is advanced or set, but each can be
+/// skewed on its own, which is what lease and leader-election logic needs to
+/// be tested against:
+///
+/// ```
+/// use hourglass_rs::{SafeTimeProvider, TimeSource};
+/// use chrono::Duration;
+///
+/// let time = SafeTimeProvider::new(TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap()));
+/// let control = time.test_control().unwrap();
+/// let leader = control.fork_node("node-a");
+/// let follower = control.fork_node("node-b");
+///
+/// follower.skew(Duration::seconds(-2));
+/// control.advance(Duration::seconds(10));
+/// assert_eq!(leader.now() - follower.now(), Duration::seconds(2));
+/// ```
+#[derive(Clone)]
+pub struct ClockNode {
+    name: Arc<str>,
+    clock: Arc<NodeClock>,
+    provider: SafeTimeProvider,
+}
+
+impl ClockNode {
+    pub(crate) fn new(name: &str, parent: Arc<TestTimeProvider>) -> Self {
+        let clock = Arc::new(NodeClock {
+            state: RwLock::new(NodeState {
+                faults: ClockFaults::new(),
+                origin: parent.now(),
+            }),
+            parent: parent.clone(),
+        });
+        Self {
+            name: name.into(),
+            provider: SafeTimeProvider::new_from_node(clock.clone(), parent),
+            clock,
+        }
+    }
+
+    /// The name given to [`fork_node`](crate::TimeControl::fork_node)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Time provider reading this node's clock, for the code running on the node
+    ///
+    /// Its [`test_control`](SafeTimeProvider::test_control) controls the
+    /// parent clock.
+    pub fn provider(&self) -> SafeTimeProvider {
+        self.provider.clone()
+    }
+
+    /// Get the current time on this node
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Get the offset from the parent clock, including accumulated drift
+    pub fn offset(&self) -> Duration {
+        self.clock.offset()
+    }
+
+    /// Step this node's clock by `by`, leaving the parent and other nodes alone
+    pub fn skew(&self, by: Duration) {
+        self.clock.rebase(|offset, drift_ppm| (offset + by, drift_ppm));
+    }
+
+    /// Set the offset from the parent clock
+    pub fn set_offset(&self, offset: Duration) {
+        self.clock.rebase(|_, drift_ppm| (offset, drift_ppm));
+    }
+
+    /// Get the drift relative to the parent clock in parts per million
+    pub fn drift_ppm(&self) -> f64 {
+        self.clock.state.read().faults.drift_ppm()
+    }
+
+    /// Run fast (positive) or slow (negative) by `ppm` parts per million from now on
+    ///
+    /// Drift accumulated so far is kept in the offset.
+    pub fn set_drift_ppm(&self, ppm: f64) {
+        self.clock.rebase(|offset, _| (offset, ppm));
+    }
+}
+
+impl std::fmt::Debug for ClockNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClockNode")
+            .field("name", &self.name)
+            .field("offset", &self.offset())
+            .field("drift_ppm", &self.drift_ppm())
+            .finish()
+    }
+}
+
+/// Clock of a [`ClockNode`], derived from the parent test clock
+pub(crate) struct NodeClock {
+    state: RwLock<NodeState>,
+    parent: Arc<TestTimeProvider>,
+}
+
+/// Offset and drift are kept as faults applied from `origin`; changing
+/// either starts a new origin so the clock never jumps by itself
+struct NodeState {
+    faults: ClockFaults,
+    origin: DateTime<Utc>,
+}
+
+impl NodeClock {
+    fn now(&self) -> DateTime<Utc> {
+        let state = self.state.read();
+        let now = self.parent.now();
+        now + state.faults.offset(state.origin, now)
+    }
+
+    fn offset(&self) -> Duration {
+        let state = self.state.read();
+        state.faults.offset(state.origin, self.parent.now())
+    }
+
+    fn rebase(&self, update: impl FnOnce(Duration, f64) -> (Duration, f64)) {
+        let mut state = self.state.write();
+        let now = self.parent.now();
+        let (offset, drift_ppm) = update(state.faults.offset(state.origin, now), state.faults.drift_ppm());
+        state.faults = ClockFaults::new().with_skew(offset).with_drift_ppm(drift_ppm);
+        state.origin = now;
+    }
+
+    /// This node's reading at parent time `time`
+    fn reading_at(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let state = self.state.read();
+        time + state.faults.offset(state.origin, time)
+    }
+}
+
+impl ClockMap for NodeClock {
+    fn to_inner(&self, reading: DateTime<Utc>) -> DateTime<Utc> {
+        let state = self.state.read();
+        state.faults.true_time(state.origin, self.parent.now(), reading)
+    }
+}
+
+/// Time provider behind a [`ClockNode`]
+///
+/// Sleeps wait on the parent clock but report their deadline as read on the node.
+pub(crate) struct NodeProvider(pub(crate) Arc<NodeClock>);
+
+impl NodeProvider {
+    fn map_sleep(&self, sleep: Sleep, deadline: DateTime<Utc>) -> Sleep {
+        sleep.mapped(deadline, self.0.clone())
+    }
+}
+
+#[async_trait]
+impl TimeProvider for NodeProvider {
+    fn now(&self) -> DateTime<Utc> {
+        self.0.now()
+    }
+
+    fn instant(&self) -> Instant {
+        self.0.parent.instant()
+    }
+
+    async fn wait(&self, duration: Duration) {
+        self.0.parent.wait(duration).await
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.0.parent.wait_until(self.0.to_inner(deadline)).await
+    }
+
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        self.0.parent.wait_labeled(label, duration).await
+    }
+
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        self.0.parent.wait_until_labeled(label, self.0.to_inner(deadline)).await
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let sleep = self.0.parent.sleep(duration);
+        let deadline = self.0.reading_at(sleep.deadline());
+        self.map_sleep(sleep, deadline)
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        self.map_sleep(self.0.parent.sleep_until(self.0.to_inner(deadline)), deadline)
+    }
+
+    fn deadline_timer(&self, deadline: DateTime<Utc>, internal: Internal) -> Sleep {
+        let timer = self.0.parent.deadline_timer(self.0.to_inner(deadline), internal);
+        self.map_sleep(timer, deadline)
+    }
+
+    fn is_test(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,126 @@
+use crate::instant::Instant;
+use crate::provider::TimeProvider;
+use crate::sleep::Sleep;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+/// Production time provider that reads the system clock shifted by a fixed offset
+///
+/// Time keeps flowing at real speed and waits take real time, so code can be
+/// rerun "as of" another date, such as the last quarter end.
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetTimeProvider {
+    offset: Duration,
+}
+
+impl OffsetTimeProvider {
+    /// Create a provider that runs `offset` ahead of (or behind, if negative) the system clock
+    pub fn new(offset: Duration) -> Self {
+        Self { offset }
+    }
+
+    /// Create a provider whose clock reads `at` right now
+    pub fn anchored(at: DateTime<Utc>) -> Self {
+        Self::new(at - Utc::now())
+    }
+
+    /// Get the offset from the system clock
+    pub fn offset(&self) -> Duration {
+        self.offset
+    }
+}
+
+#[async_trait]
+impl TimeProvider for OffsetTimeProvider {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::system_now()
+    }
+
+    async fn wait(&self, duration: Duration) {
+        self.sleep(duration).await
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.sleep_until(deadline).await
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        Sleep::real(deadline, self.now())
+    }
+
+    fn is_test(&self) -> bool {
+        false
+    }
+}
+
+/// Parse an ISO 8601 duration such as `P90D`, `-PT1H30M` or `P1W`
+///
+/// Years and months have no fixed length, so they are rejected.
+pub(crate) fn parse_iso8601_duration(s: &str) -> Option<Duration> {
+    let (negative, rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let (date, time) = match rest.split_once('T') {
+        Some((_, "")) => return None,
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    for (value, unit) in components(date)? {
+        let unit = match unit {
+            'W' => Duration::weeks(1),
+            'D' => Duration::days(1),
+            _ => return None,
+        };
+        total += scale(unit, value)?;
+    }
+    for (value, unit) in components(time.unwrap_or(""))? {
+        let unit = match unit {
+            'H' => Duration::hours(1),
+            'M' => Duration::minutes(1),
+            'S' => Duration::seconds(1),
+            _ => return None,
+        };
+        total += scale(unit, value)?;
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Split `90D12H` into `[(90.0, 'D'), (12.0, 'H')]`
+fn components(s: &str) -> Option<Vec<(f64, char)>> {
+    let mut components = Vec::new();
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c.is_ascii_alphabetic() {
+            let value = &s[start..i];
+            if value.is_empty() || !value.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            components.push((value.replace(',', ".").parse().ok()?, c));
+            start = i + 1;
+        }
+    }
+    if start != s.len() {
+        return None;
+    }
+    Some(components)
+}
+
+fn scale(unit: Duration, value: f64) -> Option<Duration> {
+    let nanos = unit.num_nanoseconds()? as f64 * value;
+    if nanos.is_finite() && nanos.abs() < i64::MAX as f64 {
+        Some(Duration::nanoseconds(nanos.round() as i64))
+    } else {
+        None
+    }
+}
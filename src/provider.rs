@@ -1,3 +1,5 @@
+use crate::instant::Instant;
+use crate::sleep::Sleep;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
@@ -5,18 +7,68 @@ use std::sync::Arc;
 /// Type alias for a shared time provider
 pub type SharedTimeProvider = Arc<dyn TimeProvider>;
 
+pub(crate) mod sealed {
+    /// Argument that only this crate can build, so methods taking it can be
+    /// neither called nor overridden outside the crate
+    #[derive(Debug, Clone, Copy)]
+    pub struct Internal(pub(crate) ());
+}
+
 /// Core trait for time providers
 #[async_trait]
 pub trait TimeProvider: Send + Sync {
     /// Get the current time
     fn now(&self) -> DateTime<Utc>;
     
+    /// Get the current monotonic instant, unaffected by wall-clock jumps
+    /// 
+    /// Defaults to the system's monotonic clock.
+    fn instant(&self) -> Instant {
+        Instant::system_now()
+    }
+    
     /// Wait for the specified duration
     async fn wait(&self, duration: Duration);
     
     /// Wait until the specified deadline
     async fn wait_until(&self, deadline: DateTime<Utc>);
     
+    /// Wait for the specified duration, tagging the wait with `label`
+    /// 
+    /// Test providers keep the label in their wait history.
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        let _ = label;
+        self.wait(duration).await
+    }
+    
+    /// Wait until the specified deadline, tagging the wait with `label`
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        let _ = label;
+        self.wait_until(deadline).await
+    }
+    
+    /// Create a sleep that completes after the specified duration
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.sleep_until(self.now() + duration)
+    }
+    
+    /// Create a sleep that completes at the specified deadline
+    /// 
+    /// Defaults to sleeping in real time until [`now`](Self::now) reaches
+    /// `deadline`, assuming the provider's clock runs at real speed.
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        Sleep::real(deadline, self.now())
+    }
+    
+    /// Create a timer for a deadline that is not a wait, such as a timeout
+    /// 
+    /// Test providers do not count it as a wait and never move the clock
+    /// for it on their own.
+    #[doc(hidden)]
+    fn deadline_timer(&self, deadline: DateTime<Utc>, _: sealed::Internal) -> Sleep {
+        self.sleep_until(deadline)
+    }
+    
     /// Check if this is a test provider
     fn is_test(&self) -> bool;
 }
\ No newline at end of file
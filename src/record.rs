@@ -0,0 +1,145 @@
+use crate::instant::Instant;
+use crate::provider::TimeProvider;
+use crate::sleep::Sleep;
+use crate::system::SystemTimeProvider;
+use crate::trace::{TraceEvent, TRACE_HEADER};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// System time provider that records every time read and wait to a trace
+///
+/// Each event is written as one line in the [`trace`](crate::trace) format
+/// and flushed straight away, so the trace survives a crash. Errors while
+/// writing the trace are ignored rather than disturbing the program.
+///
+/// Waits are written when they complete, numbered in the order they
+/// started, so replay can match overlapping waits in call order.
+///
+/// Only `now`, `wait` and `wait_until`, with their labeled forms, are
+/// traced. Sleeps, timeouts and intervals are not recorded as waits; the
+/// clock reads they make internally show up as `now` events, which replay
+/// plays back the same way.
+pub struct RecordingTimeProvider {
+    inner: SystemTimeProvider,
+    writer: Mutex<Box<dyn Write + Send>>,
+    next_seq: AtomicU64,
+}
+
+impl RecordingTimeProvider {
+    /// Record to `writer`
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        let provider = Self {
+            inner: SystemTimeProvider,
+            writer: Mutex::new(Box::new(writer)),
+            next_seq: AtomicU64::new(0),
+        };
+        provider.write_line(TRACE_HEADER);
+        provider
+    }
+
+    /// Record to a new file at `path`, replacing any existing file
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Get the current time, recording it with `label`
+    pub fn now_labeled(&self, label: &str) -> DateTime<Utc> {
+        let seq = self.start_call();
+        let value = self.inner.now();
+        self.record(TraceEvent::Now {
+            seq: Some(seq),
+            value,
+            label: label.to_string(),
+        });
+        value
+    }
+
+    /// Flush buffered trace lines
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.writer.lock().flush()
+    }
+
+    /// Number the next call in the order calls start
+    fn start_call(&self) -> u64 {
+        self.next_seq.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn record(&self, event: TraceEvent) {
+        self.write_line(&event.to_string());
+    }
+
+    fn write_line(&self, line: &str) {
+        let mut writer = self.writer.lock();
+        let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+    }
+}
+
+#[async_trait]
+impl TimeProvider for RecordingTimeProvider {
+    fn now(&self) -> DateTime<Utc> {
+        self.now_labeled("")
+    }
+
+    fn instant(&self) -> Instant {
+        self.inner.instant()
+    }
+
+    async fn wait(&self, duration: Duration) {
+        self.wait_labeled("", duration).await
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.wait_until_labeled("", deadline).await
+    }
+
+    /// Records the wait with `label` once it completes
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        let seq = self.start_call();
+        let started = self.inner.now();
+        self.inner.sleep_until(started + duration).await;
+        self.record(TraceEvent::Wait {
+            seq: Some(seq),
+            started,
+            duration,
+            completed: self.inner.now(),
+            label: label.to_string(),
+        });
+    }
+
+    /// Records the wait with `label` once it completes
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        let seq = self.start_call();
+        let started = self.inner.now();
+        self.inner.sleep_until(deadline).await;
+        self.record(TraceEvent::WaitUntil {
+            seq: Some(seq),
+            started,
+            deadline,
+            completed: self.inner.now(),
+            label: label.to_string(),
+        });
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.inner.sleep(duration)
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        self.inner.sleep_until(deadline)
+    }
+
+    fn is_test(&self) -> bool {
+        false
+    }
+}
+
+impl std::fmt::Debug for RecordingTimeProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingTimeProvider").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,281 @@
+use crate::instant::Instant;
+use crate::provider::sealed::Internal;
+use crate::provider::TimeProvider;
+use crate::sleep::Sleep;
+use crate::test::TestTimeProvider;
+use crate::trace::{self, TraceEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+/// What a [`ReplayTimeProvider`] does when a call does not match the trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DivergencePolicy {
+    /// Panic with the expected and actual calls
+    #[default]
+    Error,
+    /// Stop replaying and keep running on the test clock from the last
+    /// replayed time
+    FallBackToTestClock,
+    /// Discard recorded events until one matches the call, falling back to
+    /// the test clock once the trace is exhausted
+    Skip,
+}
+
+/// Test time provider that plays back a trace written by
+/// [`RecordingTimeProvider`](crate::RecordingTimeProvider)
+///
+/// Each `now()` returns the next recorded value and each `wait` or
+/// `wait_until` returns immediately with the clock at the recorded
+/// completion time, or where it is if overlapping waits already moved it
+/// further. Calls are matched in the order they started in the recording,
+/// not the order the trace lines were written. A call diverges when the next recorded event is of a
+/// different kind, a wait asks for a different duration or deadline, or the
+/// trace has run out; labels are not compared, so edits that move code
+/// around do not invalidate a trace.
+///
+/// The replayed times drive an internal test clock, so sleeps, intervals and
+/// timeouts follow the recorded timeline and
+/// [`TimeControl`](crate::TimeControl) still works.
+pub struct ReplayTimeProvider {
+    clock: Arc<TestTimeProvider>,
+    state: Mutex<ReplayState>,
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    events: VecDeque<TraceEvent>,
+    replayed: usize,
+    policy: DivergencePolicy,
+    diverged: bool,
+}
+
+/// The call being matched against the trace
+#[derive(Debug, Clone, Copy)]
+enum Call {
+    Now,
+    Wait(Duration),
+    WaitUntil(DateTime<Utc>),
+}
+
+impl Call {
+    fn matches(&self, event: &TraceEvent) -> bool {
+        match (self, event) {
+            (Call::Now, TraceEvent::Now { .. }) => true,
+            (Call::Wait(requested), TraceEvent::Wait { duration, .. }) => requested == duration,
+            (Call::WaitUntil(requested), TraceEvent::WaitUntil { deadline, .. }) => requested == deadline,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Call::Now => write!(f, "now()"),
+            Call::Wait(duration) => write!(f, "wait({})", duration),
+            Call::WaitUntil(deadline) => write!(f, "wait_until({})", deadline),
+        }
+    }
+}
+
+impl ReplayTimeProvider {
+    /// Replay `events`, starting the clock at the first recorded time
+    ///
+    /// Events are put back in the order the calls started when every event
+    /// has a [sequence number](TraceEvent::seq), and kept in the given order
+    /// otherwise.
+    pub fn new(mut events: Vec<TraceEvent>, policy: DivergencePolicy) -> Self {
+        if events.iter().all(|event| event.seq().is_some()) {
+            events.sort_by_key(TraceEvent::seq);
+        }
+        let start = events.first().map(|event| match event {
+            TraceEvent::Now { value, .. } => *value,
+            TraceEvent::Wait { started, .. } | TraceEvent::WaitUntil { started, .. } => *started,
+        });
+        let clock = match start {
+            Some(start) => TestTimeProvider::new(start),
+            None => TestTimeProvider::new_at_now(),
+        };
+        Self {
+            clock: Arc::new(clock),
+            state: Mutex::new(ReplayState {
+                events: events.into(),
+                replayed: 0,
+                policy,
+                diverged: false,
+            }),
+        }
+    }
+
+    /// Replay the trace file at `path`
+    ///
+    /// Parse errors are reported as [`std::io::ErrorKind::InvalidData`].
+    pub fn load(path: impl AsRef<Path>, policy: DivergencePolicy) -> std::io::Result<Self> {
+        let events = trace::read_trace(BufReader::new(File::open(path)?))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::new(events, policy))
+    }
+
+    /// Number of recorded events not yet replayed
+    pub fn remaining(&self) -> usize {
+        let state = self.state.lock();
+        if state.diverged && state.policy == DivergencePolicy::FallBackToTestClock {
+            return 0;
+        }
+        state.events.len()
+    }
+
+    /// Number of recorded events replayed so far
+    pub fn replayed(&self) -> usize {
+        self.state.lock().replayed
+    }
+
+    /// Whether any call has failed to match the trace
+    pub fn has_diverged(&self) -> bool {
+        self.state.lock().diverged
+    }
+
+    /// The test clock driven by the replay
+    pub(crate) fn clock(&self) -> Arc<TestTimeProvider> {
+        self.clock.clone()
+    }
+
+    /// Take the next recorded event for `call`, or `None` if the call
+    /// should be served by the test clock
+    fn next_event(&self, call: Call) -> Option<TraceEvent> {
+        let mut state = self.state.lock();
+        if state.diverged && state.policy == DivergencePolicy::FallBackToTestClock {
+            return None;
+        }
+        if state.events.front().is_some_and(|event| call.matches(event)) {
+            state.replayed += 1;
+            return state.events.pop_front();
+        }
+
+        state.diverged = true;
+        match state.policy {
+            DivergencePolicy::Error => {
+                let expected = match state.events.front() {
+                    Some(event) => format!("'{}'", event),
+                    None => "the end of the trace".to_string(),
+                };
+                let replayed = state.replayed;
+                drop(state);
+                panic!("time replay diverged after {} events: expected {}, got {}", replayed, expected, call);
+            }
+            DivergencePolicy::FallBackToTestClock => None,
+            DivergencePolicy::Skip => {
+                let position = state.events.iter().position(|event| call.matches(event));
+                match position {
+                    Some(position) => {
+                        state.events.drain(..position);
+                        state.replayed += 1;
+                        state.events.pop_front()
+                    }
+                    None => {
+                        state.events.clear();
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Complete a replayed wait, never moving the clock back past a later
+    /// wait that finished first
+    fn finish_wait(&self, completed: DateTime<Utc>) {
+        let now = self.clock.now();
+        if completed > now {
+            self.clock.advance(completed - now);
+        }
+    }
+
+    /// Move the test clock to a recorded time
+    fn move_clock(&self, time: DateTime<Utc>) {
+        let now = self.clock.now();
+        if time >= now {
+            self.clock.advance(time - now);
+        } else {
+            self.clock.set(time);
+        }
+    }
+}
+
+#[async_trait]
+impl TimeProvider for ReplayTimeProvider {
+    fn now(&self) -> DateTime<Utc> {
+        match self.next_event(Call::Now) {
+            Some(TraceEvent::Now { value, .. }) => {
+                self.move_clock(value);
+                value
+            }
+            _ => self.clock.now(),
+        }
+    }
+
+    fn instant(&self) -> Instant {
+        self.clock.instant()
+    }
+
+    async fn wait(&self, duration: Duration) {
+        match self.next_event(Call::Wait(duration)) {
+            Some(TraceEvent::Wait { completed, .. }) => self.finish_wait(completed),
+            _ => self.clock.wait(duration).await,
+        }
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        match self.next_event(Call::WaitUntil(deadline)) {
+            Some(TraceEvent::WaitUntil { completed, .. }) => self.finish_wait(completed),
+            _ => self.clock.wait_until(deadline).await,
+        }
+    }
+
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        match self.next_event(Call::Wait(duration)) {
+            Some(TraceEvent::Wait { completed, .. }) => self.finish_wait(completed),
+            _ => self.clock.wait_labeled(label, duration).await,
+        }
+    }
+
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        match self.next_event(Call::WaitUntil(deadline)) {
+            Some(TraceEvent::WaitUntil { completed, .. }) => self.finish_wait(completed),
+            _ => self.clock.wait_until_labeled(label, deadline).await,
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.clock.sleep(duration)
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        self.clock.sleep_until(deadline)
+    }
+
+    fn deadline_timer(&self, deadline: DateTime<Utc>, internal: Internal) -> Sleep {
+        self.clock.deadline_timer(deadline, internal)
+    }
+
+    fn is_test(&self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for ReplayTimeProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("ReplayTimeProvider")
+            .field("replayed", &state.replayed)
+            .field("remaining", &state.events.len())
+            .field("policy", &state.policy)
+            .field("diverged", &state.diverged)
+            .finish()
+    }
+}
@@ -0,0 +1,325 @@
+//! Timer backend used for real-time sleeps
+//!
+//! The backend is picked with cargo features, in this order of preference:
+//!
+//! - `tokio` (default): sleeps are tokio timers, so they need a tokio runtime
+//!   and follow `tokio::time::pause`
+//! - `async-io`: sleeps are `async_io::Timer`s, for async-std, smol and
+//!   other runtimes built on async-io
+//! - `std-thread`: sleeps are woken by a background thread, so they work on
+//!   any executor
+//!
+//! The test provider never uses the backend, so virtual-time code runs on
+//! any executor whichever feature is picked.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(not(any(feature = "tokio", feature = "async-io", feature = "std-thread")))]
+compile_error!("hourglass-rs needs one of the `tokio`, `async-io` or `std-thread` features");
+
+#[cfg(feature = "tokio")]
+use tokio_backend as backend;
+
+#[cfg(all(feature = "async-io", not(feature = "tokio")))]
+use async_io_backend as backend;
+
+// Also the fallback when no feature is picked, so the only error is the one above
+#[cfg(not(any(feature = "tokio", feature = "async-io")))]
+use thread_backend as backend;
+
+pub(crate) use backend::{RealInstant, RealTimer};
+
+/// Identifier of the async task that made a wait
+///
+/// Only tokio tasks can be told apart; with other backends it is never set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(TaskIdInner);
+
+#[cfg(feature = "tokio")]
+type TaskIdInner = tokio::task::Id;
+
+#[cfg(not(feature = "tokio"))]
+type TaskIdInner = std::convert::Infallible;
+
+impl std::fmt::Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl TaskId {
+    /// The task being polled right now, if the backend can tell
+    pub(crate) fn current() -> Option<TaskId> {
+        #[cfg(feature = "tokio")]
+        {
+            tokio::task::try_id().map(TaskId)
+        }
+        #[cfg(not(feature = "tokio"))]
+        {
+            None
+        }
+    }
+}
+
+/// Current instant on the backend's clock
+pub(crate) fn real_now() -> RealInstant {
+    RealInstant::now()
+}
+
+/// Let every other runnable task run once before carrying on
+///
+/// Works outside any runtime; under tokio it also lets the timer and I/O
+/// drivers make progress.
+pub(crate) fn yield_now() -> impl Future<Output = ()> + Send + Sync + 'static {
+    #[cfg(feature = "tokio")]
+    {
+        tokio::task::yield_now()
+    }
+    #[cfg(not(feature = "tokio"))]
+    {
+        YieldNow { yielded: false }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+struct YieldNow {
+    yielded: bool,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_backend {
+    use super::*;
+    use pin_project_lite::pin_project;
+
+    pub(crate) type RealInstant = tokio::time::Instant;
+
+    pin_project! {
+        /// A tokio timer, created on first poll so that a timer which is
+        /// never awaited does not need a runtime
+        pub(crate) struct RealTimer {
+            deadline: RealInstant,
+            #[pin]
+            sleep: Option<tokio::time::Sleep>,
+        }
+    }
+
+    impl RealTimer {
+        pub(crate) fn new(deadline: RealInstant) -> Self {
+            Self { deadline, sleep: None }
+        }
+
+        pub(crate) fn deadline(&self) -> RealInstant {
+            self.deadline
+        }
+
+        pub(crate) fn is_elapsed(&self) -> bool {
+            match &self.sleep {
+                Some(sleep) => sleep.is_elapsed(),
+                None => real_now() >= self.deadline,
+            }
+        }
+
+        pub(crate) fn reset(self: Pin<&mut Self>, deadline: RealInstant) {
+            let this = self.project();
+            *this.deadline = deadline;
+            if let Some(sleep) = this.sleep.as_pin_mut() {
+                sleep.reset(deadline);
+            }
+        }
+    }
+
+    impl Future for RealTimer {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut this = self.project();
+            if this.sleep.is_none() {
+                this.sleep.set(Some(tokio::time::sleep_until(*this.deadline)));
+            }
+            match this.sleep.as_pin_mut() {
+                Some(sleep) => sleep.poll(cx),
+                None => Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "async-io", not(feature = "tokio")))]
+mod async_io_backend {
+    use super::*;
+
+    pub(crate) type RealInstant = std::time::Instant;
+
+    /// An async-io timer
+    pub(crate) struct RealTimer {
+        deadline: RealInstant,
+        timer: async_io::Timer,
+    }
+
+    impl RealTimer {
+        pub(crate) fn new(deadline: RealInstant) -> Self {
+            Self {
+                deadline,
+                timer: async_io::Timer::at(deadline),
+            }
+        }
+
+        pub(crate) fn deadline(&self) -> RealInstant {
+            self.deadline
+        }
+
+        pub(crate) fn is_elapsed(&self) -> bool {
+            real_now() >= self.deadline
+        }
+
+        pub(crate) fn reset(mut self: Pin<&mut Self>, deadline: RealInstant) {
+            self.deadline = deadline;
+            self.timer.set_at(deadline);
+        }
+    }
+
+    impl Future for RealTimer {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            // A one-shot async-io timer stays pending once it has fired
+            if self.is_elapsed() {
+                return Poll::Ready(());
+            }
+            Pin::new(&mut self.timer).poll(cx).map(|_| ())
+        }
+    }
+}
+
+#[cfg(not(any(feature = "tokio", feature = "async-io")))]
+mod thread_backend {
+    use super::*;
+    use parking_lot::{Condvar, Mutex};
+    use std::collections::BTreeMap;
+    use std::sync::Once;
+    use std::task::Waker;
+
+    pub(crate) type RealInstant = std::time::Instant;
+
+    type TimerKey = (RealInstant, u64);
+
+    /// Wakers of pending timers, woken in deadline order by a single thread
+    struct Queue {
+        wakers: BTreeMap<TimerKey, Waker>,
+        next_id: u64,
+    }
+
+    static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+        wakers: BTreeMap::new(),
+        next_id: 0,
+    });
+    static CHANGED: Condvar = Condvar::new();
+    static START: Once = Once::new();
+
+    fn run_timer_thread() {
+        let mut queue = QUEUE.lock();
+        loop {
+            let now = real_now();
+            let later = queue.wakers.split_off(&(now, u64::MAX));
+            let due = std::mem::replace(&mut queue.wakers, later);
+            if !due.is_empty() {
+                drop(queue);
+                due.into_values().for_each(Waker::wake);
+                queue = QUEUE.lock();
+                continue;
+            }
+            match queue.wakers.keys().next() {
+                Some(&(next, _)) => {
+                    CHANGED.wait_until(&mut queue, next);
+                }
+                None => CHANGED.wait(&mut queue),
+            }
+        }
+    }
+
+    /// A timer woken by the shared timer thread
+    pub(crate) struct RealTimer {
+        deadline: RealInstant,
+        key: Option<TimerKey>,
+    }
+
+    impl RealTimer {
+        pub(crate) fn new(deadline: RealInstant) -> Self {
+            Self { deadline, key: None }
+        }
+
+        pub(crate) fn deadline(&self) -> RealInstant {
+            self.deadline
+        }
+
+        pub(crate) fn is_elapsed(&self) -> bool {
+            real_now() >= self.deadline
+        }
+
+        pub(crate) fn reset(mut self: Pin<&mut Self>, deadline: RealInstant) {
+            self.unregister();
+            self.deadline = deadline;
+        }
+
+        fn unregister(&mut self) {
+            if let Some(key) = self.key.take() {
+                QUEUE.lock().wakers.remove(&key);
+            }
+        }
+    }
+
+    impl Future for RealTimer {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.is_elapsed() {
+                self.unregister();
+                return Poll::Ready(());
+            }
+            START.call_once(|| {
+                std::thread::Builder::new()
+                    .name("hourglass-timer".into())
+                    .spawn(run_timer_thread)
+                    .expect("failed to spawn the hourglass timer thread");
+            });
+
+            let mut queue = QUEUE.lock();
+            match self.key.and_then(|key| queue.wakers.get_mut(&key)) {
+                Some(waker) => waker.clone_from(cx.waker()),
+                None => {
+                    let key = (self.deadline, queue.next_id);
+                    queue.next_id += 1;
+                    let is_head = queue.wakers.keys().next().is_none_or(|head| key < *head);
+                    queue.wakers.insert(key, cx.waker().clone());
+                    self.key = Some(key);
+                    if is_head {
+                        CHANGED.notify_one();
+                    }
+                }
+            }
+            Poll::Pending
+        }
+    }
+
+    impl Drop for RealTimer {
+        fn drop(&mut self) {
+            self.unregister();
+        }
+    }
+}
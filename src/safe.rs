@@ -1,37 +1,80 @@
 use crate::config::TimeSource;
 use crate::control::TimeControl;
-use crate::provider::SharedTimeProvider;
+use crate::event::ClockEvents;
+use crate::fault::{ClockFaults, FaultyClock};
+use crate::instant::Instant;
+use crate::interval::Interval;
+use crate::node::{NodeClock, NodeProvider};
+use crate::provider::sealed::Internal;
+use crate::provider::{SharedTimeProvider, TimeProvider};
+use crate::record::RecordingTimeProvider;
+use crate::replay::ReplayTimeProvider;
+use crate::scaled::{ScaleControl, ScaledTimeProvider};
+use crate::sleep::Sleep;
 use crate::test::TestTimeProvider;
-use chrono::{DateTime, Duration, Utc};
+use crate::timeout::Timeout;
+use crate::zoned::ZonedProvider;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::future::Future;
+use std::panic::Location;
 use std::sync::Arc;
 
 /// Production-safe time provider wrapper that prevents accidental time manipulation
 pub struct SafeTimeProvider {
     inner: SharedTimeProvider,
     test_provider: Option<Arc<TestTimeProvider>>,
+    recorder: Option<Arc<RecordingTimeProvider>>,
+    scaled: Option<Arc<ScaledTimeProvider>>,
+    /// Waits go through `inner.wait` rather than a [`Sleep`] so a recording
+    /// or replay underneath sees them
+    traced: bool,
 }
 
 impl SafeTimeProvider {
     /// Create a new SafeTimeProvider from a TimeSource
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the trace file for [`TimeSource::Record`] cannot be created,
+    /// the one for [`TimeSource::Replay`] cannot be read, or the factor of
+    /// [`TimeSource::Scaled`] is not positive.
     pub fn new(source: TimeSource) -> Self {
         match source {
             TimeSource::System => Self {
                 inner: Arc::new(crate::system::SystemTimeProvider),
                 test_provider: None,
+                recorder: None,
+                scaled: None,
+                traced: false,
             },
             TimeSource::Test(start) => {
-                let test_provider = Arc::new(TestTimeProvider::new(start));
-                Self {
-                    inner: test_provider.clone() as SharedTimeProvider,
-                    test_provider: Some(test_provider),
-                }
+                Self::new_from_test_provider(Arc::new(TestTimeProvider::new(start)))
             },
             TimeSource::TestNow => {
-                let test_provider = Arc::new(TestTimeProvider::new_at_now());
-                Self {
-                    inner: test_provider.clone() as SharedTimeProvider,
-                    test_provider: Some(test_provider),
-                }
+                Self::new_from_test_provider(Arc::new(TestTimeProvider::new_at_now()))
+            },
+            TimeSource::Record(path) => {
+                let recorder = RecordingTimeProvider::create(&path).unwrap_or_else(|e| {
+                    panic!("failed to create time trace {}: {}", path.display(), e)
+                });
+                Self::new_from_recorder(Arc::new(recorder))
+            },
+            TimeSource::Replay { path, on_divergence } => {
+                let replay = ReplayTimeProvider::load(&path, on_divergence).unwrap_or_else(|e| {
+                    panic!("failed to read time trace {}: {}", path.display(), e)
+                });
+                Self::new_from_replay(Arc::new(replay))
+            },
+            TimeSource::Scaled { start, factor } => {
+                Self::new_from_scaled(Arc::new(ScaledTimeProvider::new(start, factor)))
+            },
+            TimeSource::Offset(_) | TimeSource::Anchored(_) => Self {
+                inner: source.into_provider(),
+                test_provider: None,
+                recorder: None,
+                scaled: None,
+                traced: false,
             },
         }
     }
@@ -41,22 +84,213 @@ impl SafeTimeProvider {
         Self {
             inner: provider.clone() as SharedTimeProvider,
             test_provider: Some(provider),
+            recorder: None,
+            scaled: None,
+            traced: false,
+        }
+    }
+    
+    /// Create from an existing recording provider
+    /// 
+    /// Reads and waits made through the returned provider are recorded with
+    /// the file and line of the caller as their label. Sleeps, timeouts and
+    /// intervals are not recorded; see [`RecordingTimeProvider`].
+    pub fn new_from_recorder(recorder: Arc<RecordingTimeProvider>) -> Self {
+        Self {
+            inner: recorder.clone() as SharedTimeProvider,
+            test_provider: None,
+            recorder: Some(recorder),
+            scaled: None,
+            traced: true,
+        }
+    }
+    
+    /// Create from an existing replay provider
+    /// 
+    /// The provider is in test mode, and [`test_control`](Self::test_control)
+    /// controls the clock the replay drives.
+    pub fn new_from_replay(replay: Arc<ReplayTimeProvider>) -> Self {
+        Self {
+            test_provider: Some(replay.clock()),
+            inner: replay as SharedTimeProvider,
+            recorder: None,
+            scaled: None,
+            traced: true,
+        }
+    }
+    
+    /// Create from an existing scaled provider
+    pub fn new_from_scaled(provider: Arc<ScaledTimeProvider>) -> Self {
+        Self {
+            inner: provider.clone() as SharedTimeProvider,
+            test_provider: None,
+            recorder: None,
+            scaled: Some(provider),
+            traced: false,
+        }
+    }
+    
+    /// Create the provider of a node forked from a test clock
+    pub(crate) fn new_from_node(node: Arc<NodeClock>, parent: Arc<TestTimeProvider>) -> Self {
+        Self {
+            inner: Arc::new(NodeProvider(node)),
+            test_provider: Some(parent),
+            recorder: None,
+            scaled: None,
+            traced: false,
         }
     }
     
     /// Get the current time
+    #[track_caller]
     pub fn now(&self) -> DateTime<Utc> {
-        self.inner.now()
+        match &self.recorder {
+            Some(recorder) => recorder.now_labeled(&Location::caller().to_string()),
+            None => self.inner.now(),
+        }
+    }
+    
+    /// Get the current time in `tz`
+    pub fn now_in<Z: TimeZone>(&self, tz: &Z) -> DateTime<Z> {
+        self.now().with_timezone(tz)
+    }
+    
+    /// Get a view of this provider in the IANA time zone `tz`
+    pub fn zoned(&self, tz: Tz) -> ZonedProvider {
+        ZonedProvider::new(self.clone(), tz)
+    }
+    
+    /// Get a provider whose `now()` readings have `faults` applied
+    /// 
+    /// The returned provider shares this provider's clock, so in test mode
+    /// [`TimeControl`] still moves the true time underneath the faults.
+    pub fn with_faults(&self, faults: ClockFaults) -> SafeTimeProvider {
+        Self {
+            inner: Arc::new(FaultyClock::new(self.inner.clone(), faults)),
+            test_provider: self.test_provider.clone(),
+            recorder: None,
+            scaled: self.scaled.clone(),
+            traced: self.traced,
+        }
+    }
+    
+    /// Get the current monotonic instant
+    /// 
+    /// Use this rather than [`now`](Self::now) to measure elapsed time, since
+    /// it never goes backwards when the test clock is set to an earlier time.
+    pub fn instant(&self) -> Instant {
+        self.inner.instant()
     }
     
     /// Wait for the specified duration
-    pub async fn wait(&self, duration: Duration) {
-        self.inner.wait(duration).await
+    /// 
+    /// Unless recording or replaying, the wait is a [`Sleep`] created when the
+    /// future is first polled, so it does not allocate and concurrent waits
+    /// in [`WaitMode::Immediate`](crate::WaitMode::Immediate) add up.
+    #[track_caller]
+    pub fn wait(&self, duration: Duration) -> impl Future<Output = ()> + '_ {
+        let caller = Location::caller();
+        async move {
+            match &self.recorder {
+                Some(recorder) => recorder.wait_labeled(&caller.to_string(), duration).await,
+                None if self.traced => self.inner.wait(duration).await,
+                None => self.inner.sleep(duration).await,
+            }
+        }
     }
     
     /// Wait until the specified deadline
-    pub async fn wait_until(&self, deadline: DateTime<Utc>) {
-        self.inner.wait_until(deadline).await
+    #[track_caller]
+    pub fn wait_until(&self, deadline: DateTime<Utc>) -> impl Future<Output = ()> + '_ {
+        let caller = Location::caller();
+        async move {
+            match &self.recorder {
+                Some(recorder) => recorder.wait_until_labeled(&caller.to_string(), deadline).await,
+                None if self.traced => self.inner.wait_until(deadline).await,
+                None => self.inner.sleep_until(deadline).await,
+            }
+        }
+    }
+    
+    /// Wait for the specified duration, tagging the wait with `label`
+    /// 
+    /// In test mode the label shows up in [`TimeControl::wait_history`] and
+    /// [`TimeControl::waits_for`]; when recording it replaces the caller
+    /// location in the trace.
+    pub async fn wait_labeled(&self, label: &str, duration: Duration) {
+        self.inner.wait_labeled(label, duration).await
+    }
+    
+    /// Wait until the specified deadline, tagging the wait with `label`
+    pub async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        self.inner.wait_until_labeled(label, deadline).await
+    }
+    
+    /// Create a sleep that completes after the specified duration
+    /// 
+    /// Unlike [`wait`](Self::wait), the returned [`Sleep`] can be inspected
+    /// and moved to a new deadline with [`Sleep::reset`]. Its deadline is
+    /// fixed when it is created, so in [`WaitMode::Immediate`](crate::WaitMode::Immediate)
+    /// sleeps created up front and awaited together end at the latest
+    /// deadline rather than adding up. On the test clock a sleep released by
+    /// [`TimeControl::set`] leaves [`instant`](Self::instant) where it was.
+    pub fn sleep(&self, duration: Duration) -> Sleep {
+        self.inner.sleep(duration)
+    }
+    
+    /// Create a sleep that completes at the specified deadline
+    pub fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        self.inner.sleep_until(deadline)
+    }
+    
+    /// Block the calling thread for the specified duration
+    /// 
+    /// For synchronous code such as batch jobs and FFI callbacks. Real clocks
+    /// use `std::thread::sleep`; in test mode the thread is parked until the
+    /// test clock reaches the deadline, following the [`WaitMode`](crate::WaitMode)
+    /// like an awaited wait. Do not call this from an async task, as it
+    /// blocks the executor thread.
+    pub fn sleep_blocking(&self, duration: Duration) {
+        self.sleep(duration).wait_blocking()
+    }
+    
+    /// Block the calling thread until the specified deadline
+    /// 
+    /// See [`sleep_blocking`](Self::sleep_blocking).
+    pub fn sleep_until_blocking(&self, deadline: DateTime<Utc>) {
+        self.sleep_until(deadline).wait_blocking()
+    }
+    
+    /// Require `future` to complete within `duration` on this provider's clock
+    /// 
+    /// Returns [`Elapsed`](crate::timeout::Elapsed) if the clock passes the
+    /// deadline first. In test mode the timeout never moves the clock by
+    /// itself; use [`TimeControl::advance`] or `set` to trigger it.
+    pub fn timeout<F: Future>(&self, duration: Duration, future: F) -> Timeout<F> {
+        self.timeout_at(self.now() + duration, future)
+    }
+    
+    /// Require `future` to complete before `deadline` on this provider's clock
+    pub fn timeout_at<F: Future>(&self, deadline: DateTime<Utc>, future: F) -> Timeout<F> {
+        Timeout::new(future, self.inner.deadline_timer(deadline, Internal(())))
+    }
+    
+    /// Create an interval that ticks immediately and then once every `period`
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `period` is not positive.
+    pub fn interval(&self, period: Duration) -> Interval {
+        Interval::new(self.clone(), self.now(), period)
+    }
+    
+    /// Create an interval whose first tick fires at `start`
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `period` is not positive.
+    pub fn interval_at(&self, start: DateTime<Utc>, period: Duration) -> Interval {
+        Interval::new(self.clone(), start, period)
     }
     
     /// Check if running in test mode
@@ -64,6 +298,21 @@ impl SafeTimeProvider {
         self.inner.is_test()
     }
     
+    /// Subscribe to changes made to the test clock by [`TimeControl`]
+    /// 
+    /// Lets caches and schedulers react when a test jumps the clock. Only
+    /// the latest [`CLOCK_EVENT_CAPACITY`](crate::event::CLOCK_EVENT_CAPACITY)
+    /// unread events are kept, so a subscriber that stops reading does not
+    /// grow without bound. In production the returned stream never yields.
+    pub fn subscribe(&self) -> ClockEvents {
+        ClockEvents::new(self.test_provider.clone())
+    }
+    
+    /// Get the speed control of a scaled provider (returns None otherwise)
+    pub fn scale_control(&self) -> Option<ScaleControl> {
+        self.scaled.clone().map(ScaleControl::new)
+    }
+    
     /// Get time control for tests (returns None in production)
     /// 
     /// This method returns a TimeControl guard that allows time manipulation
@@ -80,6 +329,9 @@ impl Clone for SafeTimeProvider {
         Self {
             inner: self.inner.clone(),
             test_provider: self.test_provider.clone(),
+            recorder: self.recorder.clone(),
+            scaled: self.scaled.clone(),
+            traced: self.traced,
         }
     }
 }
\ No newline at end of file
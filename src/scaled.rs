@@ -0,0 +1,293 @@
+use crate::instant::Instant;
+use crate::provider::TimeProvider;
+use crate::runtime::{self, RealInstant};
+use crate::sleep::{self, Sleep};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+
+/// Time provider whose clock runs `factor` times faster than real time
+///
+/// `now()` is `start + factor * real_elapsed`, and a wait for an hour at a
+/// factor of 3600 takes one real second. Useful for demos and soak
+/// environments where time should keep flowing without manual control.
+pub struct ScaledTimeProvider {
+    clock: Arc<ScaledClock>,
+}
+
+/// Clock state shared with pending sleeps, so they can follow factor changes
+pub(crate) struct ScaledClock {
+    state: RwLock<ScaledState>,
+}
+
+/// The clock is piecewise linear; each factor change starts a new piece
+#[derive(Debug)]
+struct ScaledState {
+    /// Provider time at the start of the current piece
+    anchor: DateTime<Utc>,
+    /// Monotonic reading at the start of the current piece
+    anchor_elapsed: std::time::Duration,
+    /// Real time at the start of the current piece
+    real_anchor: RealInstant,
+    factor: f64,
+    /// Bumped on every factor change
+    generation: u64,
+    /// Wakers of pending sleeps, woken on the next factor change
+    sleepers: BTreeMap<u64, Waker>,
+    next_sleeper: u64,
+}
+
+impl ScaledState {
+    fn scaled_since_anchor(&self) -> std::time::Duration {
+        self.real_anchor.elapsed().mul_f64(self.factor)
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = Duration::from_std(self.scaled_since_anchor()).unwrap_or(Duration::MAX);
+        self.anchor + elapsed
+    }
+
+    /// Real instant at which the clock reads `deadline` at the current factor
+    fn real_deadline(&self, deadline: DateTime<Utc>) -> RealInstant {
+        sleep::offset_instant(self.real_anchor, unscale(deadline - self.anchor, self.factor))
+    }
+}
+
+impl ScaledClock {
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.state.read().now()
+    }
+}
+
+/// A sleep's link to the scaled clock, kept until the sleep completes or is dropped
+pub(crate) struct ScaledWait {
+    clock: Arc<ScaledClock>,
+    key: Option<u64>,
+    generation: u64,
+}
+
+impl ScaledWait {
+    /// Start following `clock`, returning the real deadline for `deadline`
+    pub(crate) fn new(clock: Arc<ScaledClock>, deadline: DateTime<Utc>) -> (Self, RealInstant) {
+        let (generation, target) = {
+            let state = clock.state.read();
+            (state.generation, state.real_deadline(deadline))
+        };
+        (Self { clock, key: None, generation }, target)
+    }
+
+    pub(crate) fn is_elapsed(&self, deadline: DateTime<Utc>) -> bool {
+        self.clock.now() >= deadline
+    }
+
+    /// Real deadline for `deadline` at the current factor
+    pub(crate) fn real_deadline(&mut self, deadline: DateTime<Utc>) -> RealInstant {
+        let state = self.clock.state.read();
+        self.generation = state.generation;
+        state.real_deadline(deadline)
+    }
+
+    /// Ask to be woken by the next factor change, returning a new real
+    /// deadline if the factor changed since it was last computed
+    pub(crate) fn track(&mut self, deadline: DateTime<Utc>, waker: &Waker) -> Option<RealInstant> {
+        let mut state = self.clock.state.write();
+        let key = *self.key.get_or_insert_with(|| {
+            state.next_sleeper += 1;
+            state.next_sleeper
+        });
+        match state.sleepers.get_mut(&key) {
+            Some(registered) => registered.clone_from(waker),
+            None => {
+                state.sleepers.insert(key, waker.clone());
+            }
+        }
+        if state.generation == self.generation {
+            return None;
+        }
+        self.generation = state.generation;
+        Some(state.real_deadline(deadline))
+    }
+
+    /// Block the calling thread until the clock reaches `deadline`
+    pub(crate) fn wait_blocking(mut self, deadline: DateTime<Utc>) {
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut target = self.real_deadline(deadline);
+        loop {
+            if let Some(rescaled) = self.track(deadline, &waker) {
+                target = rescaled;
+            }
+            let now = runtime::real_now();
+            if now >= target {
+                return;
+            }
+            std::thread::park_timeout(target.saturating_duration_since(now));
+        }
+    }
+
+    pub(crate) fn unregister(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.clock.state.write().sleepers.remove(&key);
+        }
+    }
+}
+
+impl Drop for ScaledWait {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// Waker that unparks a thread blocked in [`ScaledWait::wait_blocking`]
+struct Unpark(std::thread::Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Convert a span of scaled provider time into real time
+fn unscale(duration: Duration, scale: f64) -> Duration {
+    if scale == 1.0 {
+        return duration;
+    }
+    let nanos = match duration.num_nanoseconds() {
+        Some(nanos) => nanos as f64,
+        None => duration.num_seconds() as f64 * 1e9,
+    };
+    Duration::nanoseconds((nanos / scale) as i64)
+}
+
+impl ScaledTimeProvider {
+    /// Create a provider that starts at `start` and runs `factor` times faster than real time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not a positive, finite number.
+    pub fn new(start: DateTime<Utc>, factor: f64) -> Self {
+        assert_valid_factor(factor);
+        let state = ScaledState {
+            anchor: start,
+            anchor_elapsed: std::time::Duration::ZERO,
+            real_anchor: runtime::real_now(),
+            factor,
+            generation: 0,
+            sleepers: BTreeMap::new(),
+            next_sleeper: 0,
+        };
+        Self {
+            clock: Arc::new(ScaledClock { state: RwLock::new(state) }),
+        }
+    }
+
+    /// Get the current speed-up factor
+    pub fn factor(&self) -> f64 {
+        self.clock.state.read().factor
+    }
+
+    /// Change the speed-up factor from now on
+    ///
+    /// The clock carries on from its current reading, so it never jumps.
+    /// Sleeps that are already pending are moved to the real time at which
+    /// the clock now reaches their deadline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not a positive, finite number.
+    pub fn set_factor(&self, factor: f64) {
+        assert_valid_factor(factor);
+        let sleepers = {
+            let mut state = self.clock.state.write();
+            let real_now = runtime::real_now();
+            let scaled = real_now.duration_since(state.real_anchor).mul_f64(state.factor);
+            state.anchor += Duration::from_std(scaled).unwrap_or(Duration::MAX);
+            state.anchor_elapsed += scaled;
+            state.real_anchor = real_now;
+            state.factor = factor;
+            state.generation += 1;
+            std::mem::take(&mut state.sleepers)
+        };
+        sleepers.into_values().for_each(Waker::wake);
+    }
+}
+
+fn assert_valid_factor(factor: f64) {
+    assert!(factor.is_finite() && factor > 0.0, "scale factor must be positive and finite, got {factor}");
+}
+
+#[async_trait]
+impl TimeProvider for ScaledTimeProvider {
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    fn instant(&self) -> Instant {
+        let state = self.clock.state.read();
+        Instant::from_origin(state.anchor_elapsed + state.scaled_since_anchor())
+    }
+
+    async fn wait(&self, duration: Duration) {
+        self.sleep(duration).await
+    }
+
+    async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.sleep_until(deadline).await
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        Sleep::scaled(deadline, self.clock.clone())
+    }
+
+    fn is_test(&self) -> bool {
+        false
+    }
+}
+
+impl std::fmt::Debug for ScaledTimeProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.clock.state.read();
+        f.debug_struct("ScaledTimeProvider")
+            .field("now", &state.now())
+            .field("factor", &state.factor)
+            .finish()
+    }
+}
+
+/// Handle for changing the speed of a scaled provider at runtime
+///
+/// Returned by [`SafeTimeProvider::scale_control`](crate::SafeTimeProvider::scale_control).
+#[derive(Clone)]
+pub struct ScaleControl {
+    provider: Arc<ScaledTimeProvider>,
+}
+
+impl ScaleControl {
+    pub(crate) fn new(provider: Arc<ScaledTimeProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Get the current speed-up factor
+    pub fn factor(&self) -> f64 {
+        self.provider.factor()
+    }
+
+    /// Change the speed-up factor from now on
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not a positive, finite number.
+    pub fn set_factor(&self, factor: f64) {
+        self.provider.set_factor(factor);
+    }
+}
+
+impl std::fmt::Debug for ScaleControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScaleControl")
+            .field("factor", &self.factor())
+            .finish()
+    }
+}
@@ -0,0 +1,440 @@
+//! Cron-style schedules driven by a [`SafeTimeProvider`]
+//!
+//! Expressions use the usual five fields (`minute hour day-of-month month
+//! day-of-week`) or six with a leading seconds field. Each field accepts `*`,
+//! values, ranges, steps and lists (`*/15`, `1-5`, `MON-FRI`, `0,30`), and
+//! the day fields also accept the Quartz extensions:
+//!
+//! - `L` - last day of the month (`L-2` for two days before it)
+//! - `15W` - weekday nearest the 15th, `LW` - last weekday of the month
+//! - `5L` - last Friday of the month
+//! - `1#2` - second Monday of the month
+//!
+//! When both day fields are restricted a date matches if either does, as in
+//! classic cron. All times are evaluated in UTC.
+//!
+//! ```rust
+//! use hourglass_rs::schedule::Schedule;
+//!
+//! let month_end: Schedule = "0 0 LW * *".parse().unwrap();
+//! let next = month_end.next_after("2024-03-10T12:00:00Z".parse().unwrap());
+//! assert_eq!(next, Some("2024-03-29T00:00:00Z".parse().unwrap()));
+//! ```
+
+use crate::calendar;
+use crate::safe::SafeTimeProvider;
+use crate::sleep::Sleep;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use futures_core::Stream;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+/// How far ahead to search before deciding a schedule never fires
+const SEARCH_HORIZON_DAYS: i64 = 366 * 9;
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Error returned when a cron expression cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScheduleError {
+    message: String,
+}
+
+impl ParseScheduleError {
+    fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ParseScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid cron expression: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseScheduleError {}
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    source: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    months: u64,
+    days_of_month: DaysOfMonth,
+    days_of_week: DaysOfWeek,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DaysOfMonth {
+    /// Field was `*` or `?`
+    any: bool,
+    days: u64,
+    /// Offsets before the last day of the month (`L` is offset 0)
+    from_last: Vec<u32>,
+    /// Days whose nearest weekday matches (`15W`)
+    nearest_weekday: Vec<u32>,
+    last_weekday: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DaysOfWeek {
+    /// Field was `*` or `?`
+    any: bool,
+    /// Bit 0 is Sunday
+    days: u64,
+    /// Last given weekday of the month (`5L`)
+    last: u64,
+    /// Nth given weekday of the month (`1#2`)
+    nth: Vec<(u32, u32)>,
+}
+
+impl Schedule {
+    /// Parse a five or six field cron expression
+    pub fn parse(expression: &str) -> Result<Self, ParseScheduleError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => (1, &fields[..]),
+            6 => (parse_field(fields[0], 0, 59, &[], "seconds")?, &fields[1..]),
+            n => return Err(ParseScheduleError::new(format!("expected 5 or 6 fields, found {}", n))),
+        };
+
+        Ok(Self {
+            source: expression.trim().to_string(),
+            seconds,
+            minutes: parse_field(rest[0], 0, 59, &[], "minutes")?,
+            hours: parse_field(rest[1], 0, 23, &[], "hours")?,
+            days_of_month: parse_days_of_month(rest[2])?,
+            months: parse_field(rest[3], 1, 12, MONTH_NAMES, "months")?,
+            days_of_week: parse_days_of_week(rest[4])?,
+        })
+    }
+
+    /// The first firing strictly after `after`, or `None` if the schedule never fires
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_nanosecond(0)? + Duration::seconds(1);
+        let first_day = start.date_naive();
+
+        for offset in 0..SEARCH_HORIZON_DAYS {
+            let date = first_day + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+            let from = if offset == 0 { start.time() } else { NaiveTime::MIN };
+            if let Some(time) = self.first_time_from(from) {
+                return Some(date.and_time(time).and_utc());
+            }
+        }
+        None
+    }
+
+    /// Iterate over the firings strictly after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        std::iter::successors(self.next_after(after), move |last| self.next_after(*last))
+    }
+
+    /// Whether the schedule fires at exactly `time`
+    pub fn includes(&self, time: DateTime<Utc>) -> bool {
+        time.nanosecond() == 0
+            && bit(self.seconds, time.second())
+            && bit(self.minutes, time.minute())
+            && bit(self.hours, time.hour())
+            && self.matches_date(time.date_naive())
+    }
+
+    /// Wait on `provider` until the next firing after its current time
+    ///
+    /// Returns the firing time, or `None` without waiting if the schedule never fires.
+    pub async fn wait_next(&self, provider: &SafeTimeProvider) -> Option<DateTime<Utc>> {
+        let next = self.next_after(provider.now())?;
+        provider.wait_until(next).await;
+        Some(next)
+    }
+
+    /// Stream every firing from `provider`'s current time onwards
+    pub fn firings(&self, provider: &SafeTimeProvider) -> Firings {
+        Firings::new(self.clone(), provider.clone())
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !bit(self.months, date.month()) {
+            return false;
+        }
+        let dom = &self.days_of_month;
+        let dow = &self.days_of_week;
+        match (dom.any, dow.any) {
+            (true, true) => true,
+            (true, false) => dow.matches(date),
+            (false, true) => dom.matches(date),
+            (false, false) => dom.matches(date) || dow.matches(date),
+        }
+    }
+
+    fn first_time_from(&self, from: NaiveTime) -> Option<NaiveTime> {
+        for hour in from.hour()..24 {
+            if !bit(self.hours, hour) {
+                continue;
+            }
+            let same_hour = hour == from.hour();
+            for minute in if same_hour { from.minute() } else { 0 }..60 {
+                if !bit(self.minutes, minute) {
+                    continue;
+                }
+                let same_minute = same_hour && minute == from.minute();
+                for second in if same_minute { from.second() } else { 0 }..60 {
+                    if bit(self.seconds, second) {
+                        return NaiveTime::from_hms_opt(hour, minute, second);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ParseScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Schedule::parse(s)
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl DaysOfMonth {
+    fn matches(&self, date: NaiveDate) -> bool {
+        let day = date.day();
+        let last = last_day_of_month(date);
+        bit(self.days, day)
+            || self.from_last.iter().any(|offset| last.checked_sub(*offset) == Some(day))
+            || self
+                .nearest_weekday
+                .iter()
+                .any(|target| nearest_weekday(date, *target) == Some(day))
+            || (self.last_weekday && nearest_weekday(date, last) == Some(day))
+    }
+}
+
+impl DaysOfWeek {
+    fn matches(&self, date: NaiveDate) -> bool {
+        let weekday = date.weekday().num_days_from_sunday();
+        let day = date.day();
+        bit(self.days, weekday)
+            || (bit(self.last, weekday) && day + 7 > last_day_of_month(date))
+            || self
+                .nth
+                .iter()
+                .any(|(target, n)| *target == weekday && (day - 1) / 7 + 1 == *n)
+    }
+}
+
+/// Stream of firings returned by [`Schedule::firings`]
+///
+/// Firings are tracked from the previous one rather than from the clock, so
+/// if a test moves the clock past several firings at once they are all
+/// delivered in order, back to back.
+pub struct Firings {
+    schedule: Schedule,
+    provider: SafeTimeProvider,
+    next: Option<DateTime<Utc>>,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl Firings {
+    fn new(schedule: Schedule, provider: SafeTimeProvider) -> Self {
+        let now = provider.now();
+        let next = schedule.next_after(now);
+        let sleep = Box::pin(provider.sleep_until(next.unwrap_or(now)));
+        Self {
+            schedule,
+            provider,
+            next,
+            sleep,
+        }
+    }
+
+    /// The schedule being followed
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// The next firing, if any
+    pub fn peek(&self) -> Option<DateTime<Utc>> {
+        self.next
+    }
+
+    /// Wait for the next firing and return its scheduled time
+    pub async fn wait_next(&mut self) -> Option<DateTime<Utc>> {
+        poll_fn(|cx| self.poll_firing(cx)).await
+    }
+
+    fn poll_firing(&mut self, cx: &mut Context<'_>) -> Poll<Option<DateTime<Utc>>> {
+        let Some(due) = self.next else {
+            return Poll::Ready(None);
+        };
+        if self.sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        self.next = self.schedule.next_after(due);
+        if let Some(following) = self.next {
+            self.sleep.set(self.provider.sleep_until(following));
+        }
+        Poll::Ready(Some(due))
+    }
+}
+
+impl Stream for Firings {
+    type Item = DateTime<Utc>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_firing(cx)
+    }
+}
+
+impl std::fmt::Debug for Firings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Firings")
+            .field("schedule", &self.schedule.source)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    calendar::days_in_month(date.year(), date.month())
+}
+
+/// Day of `date`'s month that is the weekday nearest to `target`, staying within the month
+fn nearest_weekday(date: NaiveDate, target: u32) -> Option<u32> {
+    let last = last_day_of_month(date);
+    if target > last {
+        return None;
+    }
+    let day = date.with_day(target)?;
+    Some(match day.weekday() {
+        Weekday::Sat if target == 1 => 3,
+        Weekday::Sat => target - 1,
+        Weekday::Sun if target == last => target - 2,
+        Weekday::Sun => target + 1,
+        _ => target,
+    })
+}
+
+fn parse_value(token: &str, min: u32, max: u32, names: &[&str], field: &str) -> Result<u32, ParseScheduleError> {
+    let value = match names.iter().position(|name| name.eq_ignore_ascii_case(token)) {
+        Some(index) => index as u32 + min,
+        None => token
+            .parse::<u32>()
+            .map_err(|_| ParseScheduleError::new(format!("{}: unexpected '{}'", field, token)))?,
+    };
+    if value < min || value > max {
+        return Err(ParseScheduleError::new(format!(
+            "{}: {} is outside {}-{}",
+            field, value, min, max
+        )));
+    }
+    Ok(value)
+}
+
+/// Parse a standard list of values, ranges and steps into a bitmask
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], name: &str) -> Result<u64, ParseScheduleError> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        mask |= parse_part(part, min, max, names, name)?;
+    }
+    Ok(mask)
+}
+
+fn parse_part(part: &str, min: u32, max: u32, names: &[&str], field: &str) -> Result<u64, ParseScheduleError> {
+    let (range, step) = match part.split_once('/') {
+        Some((range, step)) => {
+            let step = step
+                .parse::<u32>()
+                .ok()
+                .filter(|step| *step > 0)
+                .ok_or_else(|| ParseScheduleError::new(format!("{}: invalid step '{}'", field, step)))?;
+            (range, Some(step))
+        }
+        None => (part, None),
+    };
+
+    let (start, end) = match range {
+        "*" | "?" => (min, max),
+        _ => match range.split_once('-') {
+            Some((start, end)) => (
+                parse_value(start, min, max, names, field)?,
+                parse_value(end, min, max, names, field)?,
+            ),
+            // A single value with a step runs to the end of the field
+            None => {
+                let start = parse_value(range, min, max, names, field)?;
+                (start, if step.is_some() { max } else { start })
+            }
+        },
+    };
+    if start > end {
+        return Err(ParseScheduleError::new(format!("{}: range {}-{} is reversed", field, start, end)));
+    }
+
+    Ok((start..=end)
+        .step_by(step.unwrap_or(1) as usize)
+        .fold(0, |mask, value| mask | (1 << value)))
+}
+
+fn parse_days_of_month(field: &str) -> Result<DaysOfMonth, ParseScheduleError> {
+    let mut days = DaysOfMonth {
+        any: field == "*" || field == "?",
+        ..Default::default()
+    };
+    for part in field.split(',') {
+        let upper = part.to_ascii_uppercase();
+        if upper == "LW" {
+            days.last_weekday = true;
+        } else if upper == "L" {
+            days.from_last.push(0);
+        } else if let Some(offset) = upper.strip_prefix("L-") {
+            days.from_last.push(parse_value(offset, 0, 30, &[], "day-of-month")?);
+        } else if let Some(day) = upper.strip_suffix('W') {
+            days.nearest_weekday.push(parse_value(day, 1, 31, &[], "day-of-month")?);
+        } else {
+            days.days |= parse_part(part, 1, 31, &[], "day-of-month")?;
+        }
+    }
+    Ok(days)
+}
+
+fn parse_days_of_week(field: &str) -> Result<DaysOfWeek, ParseScheduleError> {
+    let mut days = DaysOfWeek {
+        any: field == "*" || field == "?",
+        ..Default::default()
+    };
+    let weekday = |token: &str| parse_value(token, 0, 7, WEEKDAY_NAMES, "day-of-week").map(|day| day % 7);
+    for part in field.split(',') {
+        if let Some((day, n)) = part.split_once('#') {
+            let n = parse_value(n, 1, 5, &[], "day-of-week")?;
+            days.nth.push((weekday(day)?, n));
+        } else if let Some(day) = part.strip_suffix(['L', 'l']).filter(|day| !day.is_empty()) {
+            days.last |= 1 << weekday(day)?;
+        } else {
+            let mask = parse_part(part, 0, 7, WEEKDAY_NAMES, "day-of-week")?;
+            // Both 0 and 7 mean Sunday
+            days.days |= (mask | (mask >> 7)) & 0x7f;
+        }
+    }
+    Ok(days)
+}
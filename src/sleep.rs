@@ -0,0 +1,209 @@
+use crate::runtime::{self, RealInstant, RealTimer};
+use crate::scaled::{ScaledClock, ScaledWait};
+use crate::test::TestTimer;
+use chrono::{DateTime, Duration, Utc};
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Future returned by [`SafeTimeProvider::sleep`](crate::SafeTimeProvider::sleep)
+    /// that completes once the provider's clock reaches its deadline
+    ///
+    /// A `Sleep` is created without allocating and can be moved to a new
+    /// deadline with [`reset`](Sleep::reset), which makes it suitable for
+    /// hot loops and `select!` arms that restart a timer.
+    pub struct Sleep {
+        deadline: DateTime<Utc>,
+        // Converts `deadline` into a deadline for `timer`, for providers
+        // whose clock is derived from another one
+        map: Option<Arc<dyn ClockMap>>,
+        #[pin]
+        timer: Timer,
+    }
+}
+
+/// Conversion from a derived clock's readings to the clock it is built on
+pub(crate) trait ClockMap: Send + Sync {
+    /// The time on the underlying clock at which this clock reads `reading`
+    fn to_inner(&self, reading: DateTime<Utc>) -> DateTime<Utc>;
+}
+
+/// Two conversions applied one after the other
+struct Chain(Arc<dyn ClockMap>, Arc<dyn ClockMap>);
+
+impl ClockMap for Chain {
+    fn to_inner(&self, reading: DateTime<Utc>) -> DateTime<Utc> {
+        self.1.to_inner(self.0.to_inner(reading))
+    }
+}
+
+pin_project! {
+    #[project = TimerProj]
+    enum Timer {
+        /// Backed by a real timer from the runtime backend
+        Real {
+            #[pin]
+            sleep: RealTimer,
+            // Deadline on the provider clock the real timer was set for
+            deadline: DateTime<Utc>,
+        },
+        /// A real timer for a scaled clock, moved whenever the factor changes
+        Scaled {
+            #[pin]
+            sleep: RealTimer,
+            wait: ScaledWait,
+            // Deadline on the scaled clock
+            deadline: DateTime<Utc>,
+        },
+        /// Registered with a test provider's virtual clock
+        Virtual {
+            timer: TestTimer,
+        },
+    }
+}
+
+impl Sleep {
+    /// Create a sleep that waits in real time, given the provider's current time
+    pub(crate) fn real(deadline: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        let target = offset_instant(runtime::real_now(), deadline - now);
+        Self {
+            deadline,
+            map: None,
+            timer: Timer::Real {
+                sleep: RealTimer::new(target),
+                deadline,
+            },
+        }
+    }
+
+    /// Create a sleep in real time for a scaled clock
+    pub(crate) fn scaled(deadline: DateTime<Utc>, clock: Arc<ScaledClock>) -> Self {
+        let (wait, target) = ScaledWait::new(clock, deadline);
+        Self {
+            deadline,
+            map: None,
+            timer: Timer::Scaled {
+                sleep: RealTimer::new(target),
+                wait,
+                deadline,
+            },
+        }
+    }
+
+    /// Create a sleep driven by a test provider's virtual clock
+    pub(crate) fn virtual_timer(timer: TestTimer) -> Self {
+        Self {
+            deadline: timer.deadline(),
+            map: None,
+            timer: Timer::Virtual { timer },
+        }
+    }
+
+    /// Report `deadline` on a derived clock for this sleep on the clock
+    /// underneath, converting later resets with `map`
+    pub(crate) fn mapped(mut self, deadline: DateTime<Utc>, map: Arc<dyn ClockMap>) -> Self {
+        self.map = Some(match self.map.take() {
+            Some(inner) => Arc::new(Chain(map, inner)),
+            None => map,
+        });
+        self.deadline = deadline;
+        self
+    }
+
+    /// The time at which this sleep completes
+    pub fn deadline(&self) -> DateTime<Utc> {
+        self.deadline
+    }
+
+    /// Whether the deadline has been reached
+    pub fn is_elapsed(&self) -> bool {
+        match &self.timer {
+            Timer::Real { sleep, .. } => sleep.is_elapsed(),
+            Timer::Scaled { wait, deadline, .. } => wait.is_elapsed(*deadline),
+            Timer::Virtual { timer } => timer.is_elapsed(),
+        }
+    }
+
+    /// Block the calling thread until the deadline is reached
+    pub(crate) fn wait_blocking(self) {
+        match self.timer {
+            Timer::Real { sleep, .. } => {
+                std::thread::sleep(sleep.deadline().saturating_duration_since(runtime::real_now()));
+            }
+            Timer::Scaled { wait, deadline, .. } => wait.wait_blocking(deadline),
+            Timer::Virtual { timer } => timer.wait_blocking(),
+        }
+    }
+
+    /// Move this sleep to a new deadline, even if it has already completed
+    pub fn reset(self: Pin<&mut Self>, deadline: DateTime<Utc>) {
+        let this = self.project();
+        *this.deadline = deadline;
+        let inner = match this.map {
+            Some(map) => map.to_inner(deadline),
+            None => deadline,
+        };
+        match this.timer.project() {
+            TimerProj::Real { sleep, deadline } => {
+                let shift = inner - *deadline;
+                *deadline = inner;
+                let target = offset_instant(sleep.deadline(), shift);
+                sleep.reset(target);
+            }
+            TimerProj::Scaled { sleep, wait, deadline } => {
+                *deadline = inner;
+                sleep.reset(wait.real_deadline(inner));
+            }
+            TimerProj::Virtual { timer } => timer.reset(inner),
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match self.project().timer.project() {
+            TimerProj::Real { sleep, .. } => sleep.poll(cx),
+            TimerProj::Scaled { mut sleep, wait, deadline } => {
+                if let Some(target) = wait.track(*deadline, cx.waker()) {
+                    sleep.as_mut().reset(target);
+                }
+                let poll = sleep.poll(cx);
+                if poll.is_ready() {
+                    wait.unregister();
+                }
+                poll
+            }
+            TimerProj::Virtual { timer } => Pin::new(timer).poll(cx),
+        }
+    }
+}
+
+impl std::fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sleep")
+            .field("deadline", &self.deadline)
+            .field("is_elapsed", &self.is_elapsed())
+            .finish()
+    }
+}
+
+/// Shift a runtime instant by a signed chrono duration, saturating at both ends
+pub(crate) fn offset_instant(instant: RealInstant, shift: Duration) -> RealInstant {
+    match shift.to_std() {
+        Ok(forward) => instant.checked_add(forward).unwrap_or_else(|| {
+            // Roughly 30 years, the same horizon tokio uses for "never"
+            instant + std::time::Duration::from_secs(86400 * 365 * 30)
+        }),
+        Err(_) => shift
+            .abs()
+            .to_std()
+            .ok()
+            .and_then(|backward| instant.checked_sub(backward))
+            .unwrap_or(instant),
+    }
+}
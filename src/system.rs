@@ -1,7 +1,8 @@
+use crate::instant::Instant;
 use crate::provider::TimeProvider;
+use crate::sleep::Sleep;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use tokio::time;
 
 /// Production time provider that uses actual system time
 #[derive(Debug, Clone, Copy)]
@@ -13,18 +14,20 @@ impl TimeProvider for SystemTimeProvider {
         Utc::now()
     }
     
+    fn instant(&self) -> Instant {
+        Instant::system_now()
+    }
+    
     async fn wait(&self, duration: Duration) {
-        if let Ok(std_duration) = duration.to_std() {
-            time::sleep(std_duration).await;
-        }
+        self.sleep(duration).await
     }
     
     async fn wait_until(&self, deadline: DateTime<Utc>) {
-        let now = self.now();
-        if deadline > now {
-            let duration = deadline - now;
-            self.wait(duration).await;
-        }
+        self.sleep_until(deadline).await
+    }
+    
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        Sleep::real(deadline, self.now())
     }
     
     fn is_test(&self) -> bool {
@@ -1,19 +1,278 @@
+use crate::event::ClockEvent;
+use crate::instant::Instant;
+use crate::provider::sealed::Internal;
 use crate::provider::TimeProvider;
+use crate::runtime::{self, TaskId};
+use crate::sleep::Sleep;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use parking_lot::RwLock;
+use parking_lot::{Condvar, Mutex, ReentrantMutex, RwLock};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// How waits on a test provider interact with the virtual clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaitMode {
+    /// Each wait moves the clock forward itself and returns immediately
+    #[default]
+    Immediate,
+    /// Waits park until `advance` or `set` moves the clock past their deadline
+    Parked,
+    /// Waits park, but once every task is idle the clock jumps to the
+    /// earliest pending deadline and wakes that sleeper
+    ///
+    /// Idle means that for a few scheduler rounds no task has started a wait,
+    /// registered or dropped a timer, or moved the clock; reading the clock
+    /// does not count. Tasks blocked on channels, I/O or other threads are
+    /// not seen, so the clock can jump while they are still busy: use
+    /// [`WaitMode::Parked`] and move the clock by hand for those.
+    AutoAdvance,
+}
+
+/// One wait made on a test provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitRecord {
+    /// Clock time when the wait started
+    pub started_at: DateTime<Utc>,
+    /// Clock time the wait was for
+    pub deadline: DateTime<Utc>,
+    /// Requested duration
+    pub duration: Duration,
+    /// Label given to [`wait_labeled`](crate::SafeTimeProvider::wait_labeled), if any
+    pub label: Option<String>,
+    /// Task that started the wait, if it ran inside one the backend can identify
+    pub task_id: Option<TaskId>,
+    /// Clock time when the waiter was released, or `None` while it is pending
+    /// or if it was dropped early
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A move of the test clock by `advance` or `set`, kept for timelines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ClockChange {
+    pub(crate) from: DateTime<Utc>,
+    pub(crate) to: DateTime<Utc>,
+    /// Whether the clock was set rather than advanced
+    pub(crate) set: bool,
+    /// Number of waits started before the change; [`TestTimeProvider::clock_changes`]
+    /// turns it into an index into the wait history
+    pub(crate) waits_before: usize,
+}
+
+/// Saved state of a test clock, taken with [`TestTimeProvider::snapshot`]
+#[derive(Debug, Clone)]
+pub struct ClockSnapshot {
+    current_time: DateTime<Utc>,
+    total_waited: Duration,
+    wait_call_count: usize,
+    wait_history: VecDeque<(u64, WaitRecord)>,
+    clock_changes: VecDeque<ClockChange>,
+    pending_timers: Vec<PendingTimer>,
+}
+
+impl ClockSnapshot {
+    /// Clock time when the snapshot was taken
+    pub fn current_time(&self) -> DateTime<Utc> {
+        self.current_time
+    }
+    
+    /// Total duration waited when the snapshot was taken
+    pub fn total_waited(&self) -> Duration {
+        self.total_waited
+    }
+    
+    /// Number of wait calls when the snapshot was taken
+    pub fn wait_call_count(&self) -> usize {
+        self.wait_call_count
+    }
+    
+    /// Wait history when the snapshot was taken
+    pub fn wait_history(&self) -> Vec<WaitRecord> {
+        self.wait_history.iter().map(|(_, record)| record.clone()).collect()
+    }
+    
+    /// Sleepers parked on the clock when the snapshot was taken
+    pub fn pending_timers(&self) -> &[PendingTimer] {
+        &self.pending_timers
+    }
+}
+
+/// Waits and clock changes kept for [`TestTimeProvider::wait_history`] unless
+/// changed with [`TestTimeProvider::set_wait_history_limit`]
+pub const DEFAULT_WAIT_HISTORY_LIMIT: usize = 10_000;
+
+/// Consecutive polls without clock activity before the earliest sleeper
+/// considers every other task idle
+pub(crate) const IDLE_POLLS: u32 = 16;
+
+/// Real time between polls of a blocking sleep in auto-advance mode, so
+/// other threads get a chance to touch the clock
+const BLOCKING_POLL_PAUSE: std::time::Duration = std::time::Duration::from_millis(1);
 
 /// Test time provider that allows time manipulation
 pub struct TestTimeProvider {
     state: Arc<RwLock<TestState>>,
+    observers: Mutex<Observers>,
+    /// Held by `advance`, `set` and `restore` until observers have seen the
+    /// move, so events arrive in the order the clock moved; reentrant so
+    /// callbacks can move the clock themselves
+    moving: ReentrantMutex<()>,
+}
+
+/// Handle for removing a callback registered with
+/// [`TestTimeProvider::on_clock_change`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+type Observer = Arc<dyn Fn(&ClockEvent) + Send + Sync>;
+
+#[derive(Default)]
+struct Observers {
+    callbacks: Vec<(ObserverId, Observer)>,
+    next_id: u64,
+}
+
+/// Pending timers are ordered by deadline, then by registration order
+type TimerKey = (DateTime<Utc>, u64);
+
+/// A sleeper parked on the test clock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTimer {
+    /// Clock time the sleeper is waiting for
+    pub deadline: DateTime<Utc>,
+    /// Label of the wait, if it was made with a label
+    pub label: Option<String>,
+    /// Clock time when the sleep was created
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct TimerEntry {
+    waker: Waker,
+    label: Option<String>,
+    created_at: DateTime<Utc>,
 }
 
 #[derive(Debug)]
 struct TestState {
     current_time: DateTime<Utc>,
+    /// Monotonic clock reading; only ever moves forward
+    elapsed: std::time::Duration,
     total_waited: Duration,
     wait_call_count: usize,
+    /// Waits in the order they started, keyed by an id that is never reused
+    wait_history: VecDeque<(u64, WaitRecord)>,
+    next_wait_id: u64,
+    clock_changes: VecDeque<ClockChange>,
+    /// Most waits and clock changes kept, oldest dropped first
+    history_limit: usize,
+    wait_mode: WaitMode,
+    timers: BTreeMap<TimerKey, TimerEntry>,
+    next_timer_id: u64,
+    /// Bumped whenever a wait starts, a timer registers, fires or is dropped,
+    /// or the clock moves; used for idle detection
+    activity: u64,
+}
+
+impl TestState {
+    /// Remove every timer whose deadline has been reached, in deadline order
+    fn take_due_timers(&mut self) -> Vec<Waker> {
+        let mut due = Vec::new();
+        while let Some(entry) = self.timers.first_entry() {
+            if entry.key().0 > self.current_time {
+                break;
+            }
+            due.push(entry.remove().waker);
+        }
+        due.extend(self.head_waker());
+        due
+    }
+    
+    /// In auto-advance mode the earliest sleeper drives the clock, so it
+    /// must be polled whenever the head of the queue changes
+    fn head_waker(&self) -> Option<Waker> {
+        if self.wait_mode != WaitMode::AutoAdvance {
+            return None;
+        }
+        self.timers.first_key_value().map(|(_, timer)| timer.waker.clone())
+    }
+    
+    /// Parked sleepers in deadline order
+    fn pending_timers(&self) -> Vec<PendingTimer> {
+        self.timers
+            .iter()
+            .map(|((deadline, _), timer)| PendingTimer {
+                deadline: *deadline,
+                label: timer.label.clone(),
+                created_at: timer.created_at,
+            })
+            .collect()
+    }
+    
+    /// Move the wall clock, carrying the monotonic clock along for forward moves
+    fn move_to(&mut self, time: DateTime<Utc>) {
+        if let Ok(forward) = (time - self.current_time).to_std() {
+            self.elapsed += forward;
+        }
+        self.current_time = time;
+    }
+    
+    fn touch(&mut self) {
+        self.activity += 1;
+    }
+    
+    /// Record the start of a wait, returning its id
+    fn start_wait(&mut self, label: Option<&str>, duration: Duration) -> u64 {
+        self.total_waited += duration;
+        self.wait_call_count += 1;
+        self.touch();
+        let id = self.next_wait_id;
+        self.next_wait_id += 1;
+        let record = WaitRecord {
+            started_at: self.current_time,
+            deadline: self.current_time + duration,
+            duration,
+            label: label.map(str::to_string),
+            task_id: TaskId::current(),
+            completed_at: None,
+        };
+        self.wait_history.push_back((id, record));
+        self.trim_history();
+        id
+    }
+    
+    fn record_change(&mut self, from: DateTime<Utc>, set: bool) {
+        let change = ClockChange {
+            from,
+            to: self.current_time,
+            set,
+            waits_before: self.next_wait_id as usize,
+        };
+        self.clock_changes.push_back(change);
+        self.trim_history();
+    }
+    
+    fn trim_history(&mut self) {
+        while self.wait_history.len() > self.history_limit {
+            self.wait_history.pop_front();
+        }
+        while self.clock_changes.len() > self.history_limit {
+            self.clock_changes.pop_front();
+        }
+    }
+    
+    /// Mark a wait as released at the current time
+    fn complete_wait(&mut self, id: Option<u64>) {
+        let Some(id) = id else {
+            return;
+        };
+        if let Ok(index) = self.wait_history.binary_search_by_key(&id, |(id, _)| *id) {
+            self.wait_history[index].1.completed_at = Some(self.current_time);
+        }
+    }
 }
 
 impl TestTimeProvider {
@@ -22,9 +281,20 @@ impl TestTimeProvider {
         Self {
             state: Arc::new(RwLock::new(TestState {
                 current_time: start,
+                elapsed: std::time::Duration::ZERO,
                 total_waited: Duration::zero(),
                 wait_call_count: 0,
+                wait_history: VecDeque::new(),
+                next_wait_id: 0,
+                clock_changes: VecDeque::new(),
+                history_limit: DEFAULT_WAIT_HISTORY_LIMIT,
+                wait_mode: WaitMode::default(),
+                timers: BTreeMap::new(),
+                next_timer_id: 0,
+                activity: 0,
             })),
+            observers: Mutex::new(Observers::default()),
+            moving: ReentrantMutex::new(()),
         }
     }
     
@@ -35,14 +305,135 @@ impl TestTimeProvider {
     
     /// Advance time by the specified duration
     pub fn advance(&self, duration: Duration) {
-        let mut state = self.state.write();
-        state.current_time = state.current_time + duration;
+        let _moving = self.moving.lock();
+        let (from, to, due) = {
+            let mut state = self.state.write();
+            let from = state.current_time;
+            state.move_to(from + duration);
+            state.record_change(from, false);
+            state.touch();
+            (from, state.current_time, state.take_due_timers())
+        };
+        if to < from {
+            self.notify(ClockEvent::WentBackwards { from, to });
+        } else {
+            self.notify(ClockEvent::Advanced { from, to });
+        }
+        due.into_iter().for_each(Waker::wake);
     }
     
     /// Set time to a specific value
+    ///
+    /// The monotonic clock behind [`instant`](TimeProvider::instant) is not
+    /// affected, even when moving forward: a sleeper released by `set` reads
+    /// no elapsed time on `instant()` across its wait. Use
+    /// [`advance`](Self::advance) to move both clocks.
     pub fn set(&self, time: DateTime<Utc>) {
-        let mut state = self.state.write();
-        state.current_time = time;
+        let _moving = self.moving.lock();
+        let (from, due) = {
+            let mut state = self.state.write();
+            let from = state.current_time;
+            state.current_time = time;
+            state.record_change(from, true);
+            state.touch();
+            (from, state.take_due_timers())
+        };
+        self.notify(set_event(from, time));
+        due.into_iter().for_each(Waker::wake);
+    }
+    
+    /// Save the clock time, wait statistics, wait history and pending sleepers
+    pub fn snapshot(&self) -> ClockSnapshot {
+        let state = self.state.read();
+        ClockSnapshot {
+            current_time: state.current_time,
+            total_waited: state.total_waited,
+            wait_call_count: state.wait_call_count,
+            wait_history: state.wait_history.clone(),
+            clock_changes: state.clock_changes.clone(),
+            pending_timers: state.pending_timers(),
+        }
+    }
+    
+    /// Put the clock time, wait statistics and wait history back to `snapshot`
+    ///
+    /// The clock is set like [`set`](Self::set), so observers see the move
+    /// and the monotonic clock is not rewound. Sleepers are never cancelled
+    /// or re-armed: whatever is parked when restoring, including sleepers
+    /// registered after the snapshot, stays parked on its original deadline
+    /// and fires once the clock reaches it again, while sleepers that already
+    /// fired stay fired. Waits started after the snapshot are left out of the
+    /// restored history even if they complete later.
+    pub fn restore(&self, snapshot: &ClockSnapshot) {
+        let _moving = self.moving.lock();
+        let (from, due) = {
+            let mut state = self.state.write();
+            let from = state.current_time;
+            state.current_time = snapshot.current_time;
+            state.total_waited = snapshot.total_waited;
+            state.wait_call_count = snapshot.wait_call_count;
+            state.wait_history = snapshot.wait_history.clone();
+            state.clock_changes = snapshot.clock_changes.clone();
+            state.record_change(from, true);
+            state.trim_history();
+            state.touch();
+            (from, state.take_due_timers())
+        };
+        self.notify(set_event(from, snapshot.current_time));
+        due.into_iter().for_each(Waker::wake);
+    }
+    
+    /// Call `callback` whenever [`advance`](Self::advance) or [`set`](Self::set) moves the clock
+    ///
+    /// Callbacks run synchronously on the thread that moved the clock, in
+    /// registration order, before any sleeper is woken. Moves made from
+    /// several threads are reported one at a time, in the order they
+    /// happened. Waits that move the clock themselves in
+    /// [`WaitMode::Immediate`] or [`WaitMode::AutoAdvance`] are not reported.
+    pub fn on_clock_change(&self, callback: impl Fn(&ClockEvent) + Send + Sync + 'static) -> ObserverId {
+        let mut observers = self.observers.lock();
+        let id = ObserverId(observers.next_id);
+        observers.next_id += 1;
+        observers.callbacks.push((id, Arc::new(callback)));
+        id
+    }
+    
+    /// Stop calling a callback registered with [`on_clock_change`](Self::on_clock_change)
+    ///
+    /// Returns whether the callback was still registered.
+    pub fn remove_observer(&self, id: ObserverId) -> bool {
+        let mut observers = self.observers.lock();
+        let before = observers.callbacks.len();
+        observers.callbacks.retain(|(observer, _)| *observer != id);
+        observers.callbacks.len() != before
+    }
+    
+    fn notify(&self, event: ClockEvent) {
+        // Call outside the lock so callbacks can register or remove observers
+        let callbacks: Vec<Observer> = {
+            let observers = self.observers.lock();
+            observers.callbacks.iter().map(|(_, callback)| callback.clone()).collect()
+        };
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+    
+    /// Get the current wait mode
+    pub fn wait_mode(&self) -> WaitMode {
+        self.state.read().wait_mode
+    }
+    
+    /// Change how subsequent waits interact with the clock
+    pub fn set_wait_mode(&self, mode: WaitMode) {
+        let head = {
+            let mut state = self.state.write();
+            state.wait_mode = mode;
+            state.head_waker()
+        };
+        if let Some(waker) = head {
+            waker.wake();
+        }
     }
     
     /// Get the total duration waited
@@ -55,12 +446,80 @@ impl TestTimeProvider {
         let mut state = self.state.write();
         state.total_waited = Duration::zero();
         state.wait_call_count = 0;
+        state.wait_history.clear();
+        state.clock_changes.clear();
     }
     
     /// Get the number of wait calls
     pub fn wait_call_count(&self) -> usize {
         self.state.read().wait_call_count
     }
+    
+    /// Get every wait since creation or last reset, in the order they started
+    /// 
+    /// Only the most recent waits are kept; see [`set_wait_history_limit`](Self::set_wait_history_limit).
+    pub fn wait_history(&self) -> Vec<WaitRecord> {
+        self.state.read().wait_history.iter().map(|(_, record)| record.clone()).collect()
+    }
+    
+    /// Keep at most `limit` waits, and as many clock changes, dropping the oldest first
+    /// 
+    /// Defaults to [`DEFAULT_WAIT_HISTORY_LIMIT`]. The wait statistics keep
+    /// counting every wait.
+    pub fn set_wait_history_limit(&self, limit: usize) {
+        let mut state = self.state.write();
+        state.history_limit = limit;
+        state.trim_history();
+    }
+    
+    /// Get the moves made by `advance` and `set` since creation or last reset,
+    /// with `waits_before` as an index into [`wait_history`](Self::wait_history)
+    pub(crate) fn clock_changes(&self) -> Vec<ClockChange> {
+        let state = self.state.read();
+        state
+            .clock_changes
+            .iter()
+            .map(|change| ClockChange {
+                waits_before: state
+                    .wait_history
+                    .partition_point(|(id, _)| (*id as usize) < change.waits_before),
+                ..change.clone()
+            })
+            .collect()
+    }
+    
+    /// Counter bumped whenever a task starts a wait or a timer or the clock moves
+    pub(crate) fn activity(&self) -> u64 {
+        self.state.read().activity
+    }
+    
+    /// Get the sleepers parked on the clock, earliest deadline first
+    pub fn pending_timers(&self) -> Vec<PendingTimer> {
+        self.state.read().pending_timers()
+    }
+    
+    /// Get the earliest deadline of a parked sleeper
+    pub fn next_deadline(&self) -> Option<DateTime<Utc>> {
+        self.state.read().timers.first_key_value().map(|((deadline, _), _)| *deadline)
+    }
+    
+    /// Get the number of sleepers parked on the clock
+    pub fn pending_count(&self) -> usize {
+        self.state.read().timers.len()
+    }
+    
+    /// Create a sleep that is recorded as a wait
+    fn start_wait(&self, label: Option<&str>, duration: Duration) -> Sleep {
+        let (deadline, id) = {
+            let mut state = self.state.write();
+            let id = state.start_wait(label, duration);
+            (state.current_time + duration, id)
+        };
+        let mut timer = TestTimer::new(self.state.clone(), deadline);
+        timer.record = Some(id);
+        timer.label = label.map(str::to_string);
+        Sleep::virtual_timer(timer)
+    }
 }
 
 #[async_trait]
@@ -69,27 +528,334 @@ impl TimeProvider for TestTimeProvider {
         self.state.read().current_time
     }
     
+    fn instant(&self) -> Instant {
+        Instant::from_origin(self.state.read().elapsed)
+    }
+    
     async fn wait(&self, duration: Duration) {
-        {
-            let mut state = self.state.write();
-            state.current_time = state.current_time + duration;
-            state.total_waited = state.total_waited + duration;
-            state.wait_call_count += 1;
-        } // Lock is dropped here
-        
-        // Yield to allow other tasks to run
-        tokio::task::yield_now().await;
+        self.sleep(duration).await
     }
     
     async fn wait_until(&self, deadline: DateTime<Utc>) {
+        self.sleep_until(deadline).await
+    }
+    
+    async fn wait_labeled(&self, label: &str, duration: Duration) {
+        self.start_wait(Some(label), duration).await
+    }
+    
+    async fn wait_until_labeled(&self, label: &str, deadline: DateTime<Utc>) {
+        let now = self.now();
+        if deadline > now {
+            self.start_wait(Some(label), deadline - now).await
+        } else {
+            self.sleep_until(deadline).await
+        }
+    }
+    
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.start_wait(None, duration)
+    }
+    
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
         let now = self.now();
         if deadline > now {
-            let duration = deadline - now;
-            self.wait(duration).await;
+            return self.sleep(deadline - now);
         }
+        Sleep::virtual_timer(TestTimer::new(self.state.clone(), deadline))
+    }
+    
+    /// Only fires when something else moves the clock, even in
+    /// [`WaitMode::Immediate`]
+    fn deadline_timer(&self, deadline: DateTime<Utc>, _: Internal) -> Sleep {
+        let mut timer = TestTimer::new(self.state.clone(), deadline);
+        timer.passive = true;
+        Sleep::virtual_timer(timer)
     }
     
     fn is_test(&self) -> bool {
         true
     }
-}
\ No newline at end of file
+}
+
+/// Timer on the virtual clock behind a [`Sleep`]
+///
+/// In [`WaitMode::Immediate`] the first poll moves the clock to the deadline;
+/// otherwise the timer parks in the provider's queue until the clock gets there.
+pub(crate) struct TestTimer {
+    state: Arc<RwLock<TestState>>,
+    deadline: DateTime<Utc>,
+    key: Option<TimerKey>,
+    fired: bool,
+    /// Never moves the clock by itself in immediate mode
+    passive: bool,
+    /// Wait history entry to complete when the timer fires
+    record: Option<u64>,
+    label: Option<String>,
+    created_at: DateTime<Utc>,
+    /// Activity counter seen on the previous poll and how long it has been stable
+    seen_activity: u64,
+    idle_polls: u32,
+    /// Pending yield while this timer lets other tasks run
+    yielding: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync>>>,
+    /// Polled by [`wait_blocking`](Self::wait_blocking), which pauses between
+    /// polls itself instead of yielding
+    blocking: bool,
+}
+
+impl TestTimer {
+    fn new(state: Arc<RwLock<TestState>>, deadline: DateTime<Utc>) -> Self {
+        let created_at = state.read().current_time;
+        Self {
+            state,
+            deadline,
+            key: None,
+            fired: false,
+            passive: false,
+            record: None,
+            label: None,
+            created_at,
+            seen_activity: 0,
+            idle_polls: 0,
+            yielding: None,
+            blocking: false,
+        }
+    }
+    
+    pub(crate) fn deadline(&self) -> DateTime<Utc> {
+        self.deadline
+    }
+    
+    pub(crate) fn is_elapsed(&self) -> bool {
+        self.fired || self.state.read().current_time >= self.deadline
+    }
+    
+    /// Re-arm the timer for a new deadline; it registers again on its next poll
+    ///
+    /// A wait that is moved no longer completes its original history entry.
+    pub(crate) fn reset(&mut self, deadline: DateTime<Utc>) {
+        self.unregister();
+        self.record = None;
+        self.deadline = deadline;
+        self.fired = false;
+        self.idle_polls = 0;
+        self.yielding = None;
+    }
+    
+    /// Park the calling thread until the timer fires
+    ///
+    /// Follows the wait mode like an awaited timer. In auto-advance mode the
+    /// earliest sleeper wakes up every millisecond to check whether the clock
+    /// is idle; the others stay parked until they become the earliest.
+    pub(crate) fn wait_blocking(mut self) {
+        self.blocking = true;
+        let parker = Arc::new(Parker::default());
+        let waker = Waker::from(parker.clone());
+        let mut cx = Context::from_waker(&waker);
+        while Pin::new(&mut self).poll(&mut cx).is_pending() {
+            let driving = {
+                let state = self.state.read();
+                state.wait_mode == WaitMode::AutoAdvance
+                    && self.key.is_some_and(|key| is_head(&state, key))
+            };
+            parker.park(driving.then_some(BLOCKING_POLL_PAUSE));
+        }
+    }
+    
+    /// Track whether anything touched the clock since the last poll
+    fn observe_idle(&mut self, activity: u64) -> bool {
+        if activity == self.seen_activity {
+            self.idle_polls += 1;
+        } else {
+            self.seen_activity = activity;
+            self.idle_polls = 0;
+        }
+        self.idle_polls >= IDLE_POLLS
+    }
+    
+    /// Yield behind every other runnable task before looking at the clock again
+    fn poll_yield(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let yielding = self
+            .yielding
+            .get_or_insert_with(|| Box::pin(runtime::yield_now()));
+        let poll = yielding.as_mut().poll(cx);
+        if poll.is_ready() {
+            self.yielding = None;
+        }
+        poll
+    }
+    
+    /// Stay pending but get polled again once other tasks have run
+    fn spin(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.blocking {
+            return Poll::Pending;
+        }
+        if self.poll_yield(cx).is_ready() {
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+    
+    /// Move the clock to our deadline and release everything due with us
+    fn fire(&mut self, state: &mut TestState) -> Vec<Waker> {
+        if state.current_time < self.deadline {
+            state.move_to(self.deadline);
+        }
+        self.fired = true;
+        state.complete_wait(self.record.take());
+        state.touch();
+        state.take_due_timers()
+    }
+    
+    fn unregister(&mut self) {
+        if let Some(key) = self.key.take() {
+            let head = {
+                let mut state = self.state.write();
+                let was_head = is_head(&state, key);
+                state.timers.remove(&key);
+                state.touch();
+                if was_head { state.head_waker() } else { None }
+            };
+            if let Some(waker) = head {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Future for TestTimer {
+    type Output = ();
+    
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        if this.yielding.is_some() && this.poll_yield(cx).is_pending() {
+            return Poll::Pending;
+        }
+        if this.fired {
+            return Poll::Ready(());
+        }
+        let shared = this.state.clone();
+        let mut state = shared.write();
+        
+        match this.key {
+            // Not registered yet: complete right away if the deadline already passed
+            None => {
+                if state.current_time >= this.deadline {
+                    this.fired = true;
+                    state.complete_wait(this.record.take());
+                    return Poll::Ready(());
+                }
+                if state.wait_mode == WaitMode::Immediate && !this.passive {
+                    // Jump the clock ourselves, then yield to allow other tasks to run
+                    let due = this.fire(&mut state);
+                    drop(state);
+                    due.into_iter().for_each(Waker::wake);
+                    if this.blocking {
+                        return Poll::Ready(());
+                    }
+                    return this.spin(cx);
+                }
+                let key = (this.deadline, state.next_timer_id);
+                state.next_timer_id += 1;
+                let timer = TimerEntry {
+                    waker: cx.waker().clone(),
+                    label: this.label.clone(),
+                    created_at: this.created_at,
+                };
+                state.timers.insert(key, timer);
+                state.touch();
+                this.key = Some(key);
+                this.seen_activity = state.activity;
+                if state.wait_mode == WaitMode::AutoAdvance && is_head(&state, key) {
+                    drop(state);
+                    return this.spin(cx);
+                }
+                Poll::Pending
+            }
+            // Registered: the timer is removed from the map once it fires
+            Some(key) => match state.timers.get_mut(&key) {
+                Some(timer) => {
+                    timer.waker.clone_from(cx.waker());
+                    if state.wait_mode != WaitMode::AutoAdvance || !is_head(&state, key) {
+                        return Poll::Pending;
+                    }
+                    
+                    // We are the earliest sleeper: keep yielding until nothing
+                    // else has touched the clock for a while, then jump to our deadline
+                    if !this.observe_idle(state.activity) {
+                        drop(state);
+                        return this.spin(cx);
+                    }
+                    state.timers.remove(&key);
+                    this.key = None;
+                    let due = this.fire(&mut state);
+                    drop(state);
+                    due.into_iter().for_each(Waker::wake);
+                    Poll::Ready(())
+                }
+                None => {
+                    this.key = None;
+                    this.fired = true;
+                    state.complete_wait(this.record.take());
+                    state.touch();
+                    Poll::Ready(())
+                }
+            },
+        }
+    }
+}
+
+/// Waker that releases a thread blocked in [`TestTimer::wait_blocking`]
+#[derive(Default)]
+struct Parker {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Parker {
+    /// Block until woken, or for at most `pause` when given
+    fn park(&self, pause: Option<std::time::Duration>) {
+        let mut woken = self.woken.lock();
+        match pause {
+            Some(pause) => {
+                self.condvar.wait_for(&mut woken, pause);
+            }
+            None => {
+                while !*woken {
+                    self.condvar.wait(&mut woken);
+                }
+            }
+        }
+        *woken = false;
+    }
+}
+
+impl Wake for Parker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    
+    fn wake_by_ref(self: &Arc<Self>) {
+        *self.woken.lock() = true;
+        self.condvar.notify_one();
+    }
+}
+
+/// Event for setting the clock from `from` to `to`
+fn set_event(from: DateTime<Utc>, to: DateTime<Utc>) -> ClockEvent {
+    if to < from {
+        ClockEvent::WentBackwards { from, to }
+    } else {
+        ClockEvent::Set { from, to }
+    }
+}
+
+fn is_head(state: &TestState, key: TimerKey) -> bool {
+    state.timers.first_key_value().is_some_and(|(head, _)| *head == key)
+}
+
+impl Drop for TestTimer {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
@@ -0,0 +1,83 @@
+use crate::sleep::Sleep;
+use chrono::{DateTime, Utc};
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Error returned when a [`Timeout`] reaches its deadline first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed {
+    deadline: DateTime<Utc>,
+}
+
+impl Elapsed {
+    /// The deadline that was reached
+    pub fn deadline(&self) -> DateTime<Utc> {
+        self.deadline
+    }
+}
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline {} has elapsed", self.deadline)
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+pin_project! {
+    /// Future returned by [`SafeTimeProvider::timeout`](crate::SafeTimeProvider::timeout)
+    ///
+    /// Resolves to the inner future's output, or to [`Elapsed`] if the
+    /// provider's clock reaches the deadline before the future completes.
+    /// In test mode the deadline only passes when the clock is moved, so
+    /// `TimeControl::advance` can trigger the timeout.
+    #[derive(Debug)]
+    pub struct Timeout<F> {
+        #[pin]
+        future: F,
+        #[pin]
+        delay: Sleep,
+    }
+}
+
+impl<F> Timeout<F> {
+    pub(crate) fn new(future: F, delay: Sleep) -> Self {
+        Self { future, delay }
+    }
+
+    /// The deadline for the inner future
+    pub fn deadline(&self) -> DateTime<Utc> {
+        self.delay.deadline()
+    }
+
+    /// Get a reference to the inner future
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Consume the timeout, returning the inner future
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        // Give the future a chance to finish before checking the deadline
+        if let Poll::Ready(output) = this.future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        let deadline = this.delay.deadline();
+        match this.delay.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed { deadline })),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
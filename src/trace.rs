@@ -0,0 +1,286 @@
+//! Line-delimited trace of time reads and waits
+//!
+//! Written by [`RecordingTimeProvider`](crate::RecordingTimeProvider), one
+//! event per line. Fields are separated by single spaces and the call-site
+//! label takes the rest of the line:
+//!
+//! ```text
+//! # hourglass trace v2
+//! now 0 2024-01-01T09:00:00.000120Z src/billing.rs:42:23
+//! wait 1 2024-01-01T09:00:00.000130Z 5s 2024-01-01T09:00:05.001212Z src/billing.rs:43:14
+//! wait_until 2 2024-01-01T09:00:05.0013Z 2024-01-01T09:01:00Z 2024-01-01T09:01:00.000914Z src/billing.rs:51:9
+//! ```
+//!
+//! Wait events are written when the wait completes, so `started` and
+//! `completed` capture how long it actually took. The number after the kind
+//! counts calls in the order they started, so overlapping waits, which
+//! finish out of order, can be put back in call order. Version 1 traces
+//! have no such number and are read in line order.
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::io::BufRead;
+
+/// First line of every trace
+pub const TRACE_HEADER: &str = "# hourglass trace v2";
+
+/// One recorded time operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A read of the current time
+    Now {
+        /// Position of the call among all traced calls, if recorded
+        seq: Option<u64>,
+        /// The time that was returned
+        value: DateTime<Utc>,
+        /// Where the read happened
+        label: String,
+    },
+    /// A wait for a duration
+    Wait {
+        /// Position of the call among all traced calls, if recorded
+        seq: Option<u64>,
+        /// When the wait started
+        started: DateTime<Utc>,
+        /// The requested duration
+        duration: Duration,
+        /// When the wait returned
+        completed: DateTime<Utc>,
+        /// Where the wait happened
+        label: String,
+    },
+    /// A wait for a deadline
+    WaitUntil {
+        /// Position of the call among all traced calls, if recorded
+        seq: Option<u64>,
+        /// When the wait started
+        started: DateTime<Utc>,
+        /// The requested deadline
+        deadline: DateTime<Utc>,
+        /// When the wait returned
+        completed: DateTime<Utc>,
+        /// Where the wait happened
+        label: String,
+    },
+}
+
+impl TraceEvent {
+    /// The call-site label of the event
+    pub fn label(&self) -> &str {
+        match self {
+            TraceEvent::Now { label, .. } | TraceEvent::Wait { label, .. } | TraceEvent::WaitUntil { label, .. } => label,
+        }
+    }
+
+    /// Position of the call among all traced calls, counted when it started
+    ///
+    /// Missing for events read from version 1 traces.
+    pub fn seq(&self) -> Option<u64> {
+        match self {
+            TraceEvent::Now { seq, .. } | TraceEvent::Wait { seq, .. } | TraceEvent::WaitUntil { seq, .. } => *seq,
+        }
+    }
+
+    /// The name used for this kind of event in the trace
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TraceEvent::Now { .. } => "now",
+            TraceEvent::Wait { .. } => "wait",
+            TraceEvent::WaitUntil { .. } => "wait_until",
+        }
+    }
+
+    /// Parse a single trace line
+    pub fn parse(line: &str) -> Result<Self, ParseTraceError> {
+        let mut fields = Fields { rest: line.trim_end_matches(['\r', '\n']) };
+        let kind = fields.next()?;
+        let seq = fields.seq()?;
+        let event = match kind {
+            "now" => TraceEvent::Now {
+                seq,
+                value: fields.time()?,
+                label: fields.label(),
+            },
+            "wait" => TraceEvent::Wait {
+                seq,
+                started: fields.time()?,
+                duration: parse_duration(fields.next()?)?,
+                completed: fields.time()?,
+                label: fields.label(),
+            },
+            "wait_until" => TraceEvent::WaitUntil {
+                seq,
+                started: fields.time()?,
+                deadline: fields.time()?,
+                completed: fields.time()?,
+                label: fields.label(),
+            },
+            other => return Err(ParseTraceError::new(format!("unknown event '{}'", other))),
+        };
+        Ok(event)
+    }
+}
+
+impl std::fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.kind())?;
+        if let Some(seq) = self.seq() {
+            write!(f, " {}", seq)?;
+        }
+        match self {
+            TraceEvent::Now { value, .. } => write!(f, " {}", format_time(*value))?,
+            TraceEvent::Wait { started, duration, completed, .. } => write!(
+                f,
+                " {} {} {}",
+                format_time(*started),
+                format_duration(*duration),
+                format_time(*completed)
+            )?,
+            TraceEvent::WaitUntil { started, deadline, completed, .. } => write!(
+                f,
+                " {} {} {}",
+                format_time(*started),
+                format_time(*deadline),
+                format_time(*completed)
+            )?,
+        }
+        let label = self.label();
+        if label.is_empty() {
+            Ok(())
+        } else {
+            write!(f, " {}", label.replace(['\r', '\n'], " "))
+        }
+    }
+}
+
+impl std::str::FromStr for TraceEvent {
+    type Err = ParseTraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TraceEvent::parse(s)
+    }
+}
+
+/// Error returned when a trace cannot be read
+#[derive(Debug)]
+pub struct ParseTraceError {
+    line: Option<usize>,
+    message: String,
+}
+
+impl ParseTraceError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// The 1-based line the error was found on, if known
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+impl std::fmt::Display for ParseTraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "invalid trace at line {}: {}", line, self.message),
+            None => write!(f, "invalid trace: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseTraceError {}
+
+/// Read every event from a trace, skipping blank and `#` comment lines
+pub fn read_trace(reader: impl BufRead) -> Result<Vec<TraceEvent>, ParseTraceError> {
+    let mut events = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| ParseTraceError::new(e.to_string()).at_line(index + 1))?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        events.push(TraceEvent::parse(&line).map_err(|e| e.at_line(index + 1))?);
+    }
+    Ok(events)
+}
+
+struct Fields<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Fields<'a> {
+    fn next(&mut self) -> Result<&'a str, ParseTraceError> {
+        let (field, rest) = self.rest.split_once(' ').unwrap_or((self.rest, ""));
+        if field.is_empty() {
+            return Err(ParseTraceError::new("missing field"));
+        }
+        self.rest = rest;
+        Ok(field)
+    }
+
+    /// Take the sequence number if the next field is one; timestamps are
+    /// never all digits
+    fn seq(&mut self) -> Result<Option<u64>, ParseTraceError> {
+        let field = self.rest.split_once(' ').map_or(self.rest, |(field, _)| field);
+        if field.is_empty() || !field.bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(None);
+        }
+        let field = self.next()?;
+        field
+            .parse()
+            .map(Some)
+            .map_err(|_| ParseTraceError::new(format!("invalid sequence number '{}'", field)))
+    }
+
+    fn time(&mut self) -> Result<DateTime<Utc>, ParseTraceError> {
+        let field = self.next()?;
+        DateTime::parse_from_rfc3339(field)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|_| ParseTraceError::new(format!("invalid timestamp '{}'", field)))
+    }
+
+    fn label(&mut self) -> String {
+        std::mem::take(&mut self.rest).to_string()
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Format as seconds with up to nine decimals, e.g. `5s` or `-0.25s`
+fn format_duration(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let duration = duration.abs();
+    let seconds = duration.num_seconds();
+    let nanos = duration.subsec_nanos();
+    if nanos == 0 {
+        format!("{}{}s", sign, seconds)
+    } else {
+        let fraction = format!("{:09}", nanos);
+        format!("{}{}.{}s", sign, seconds, fraction.trim_end_matches('0'))
+    }
+}
+
+fn parse_duration(field: &str) -> Result<Duration, ParseTraceError> {
+    let error = || ParseTraceError::new(format!("invalid duration '{}'", field));
+    let number = field.strip_suffix('s').ok_or_else(error)?;
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    let (seconds, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(error());
+    }
+    let seconds: i64 = seconds.parse().map_err(|_| error())?;
+    let nanos: i64 = format!("{:0<9}", fraction).parse().map_err(|_| error())?;
+    let duration = Duration::seconds(seconds) + Duration::nanoseconds(nanos);
+    Ok(if negative { -duration } else { duration })
+}
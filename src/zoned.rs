@@ -0,0 +1,94 @@
+use crate::safe::SafeTimeProvider;
+use crate::sleep::Sleep;
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// Longest stretch of local time a transition can skip (Samoa dropped a whole day)
+const MAX_GAP_MINUTES: i64 = 48 * 60;
+
+/// A view of a [`SafeTimeProvider`] in an IANA time zone
+///
+/// The underlying clock stays in UTC, so advancing or setting it through
+/// [`TimeControl`](crate::TimeControl) moves every zoned view consistently,
+/// including across daylight saving transitions.
+#[derive(Clone)]
+pub struct ZonedProvider {
+    provider: SafeTimeProvider,
+    tz: Tz,
+}
+
+impl ZonedProvider {
+    /// Create a view of `provider` in `tz`
+    pub fn new(provider: SafeTimeProvider, tz: Tz) -> Self {
+        Self { provider, tz }
+    }
+
+    /// The time zone of this view
+    pub fn timezone(&self) -> Tz {
+        self.tz
+    }
+
+    /// The underlying provider
+    pub fn provider(&self) -> &SafeTimeProvider {
+        &self.provider
+    }
+
+    /// Get the current local time
+    pub fn now(&self) -> DateTime<Tz> {
+        self.provider.now().with_timezone(&self.tz)
+    }
+
+    /// Get the current local date
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// Convert a UTC time to local time in this zone
+    pub fn to_local(&self, time: DateTime<Utc>) -> DateTime<Tz> {
+        time.with_timezone(&self.tz)
+    }
+
+    /// The first instant at which the local clock reads `local` or later
+    ///
+    /// A local time skipped by a forward transition resolves to the moment
+    /// of the transition, and a repeated local time resolves to its first
+    /// occurrence.
+    pub fn resolve_local(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self.tz.from_local_datetime(&local) {
+            LocalResult::Single(time) => time.with_timezone(&Utc),
+            LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+            LocalResult::None => self.end_of_gap(local),
+        }
+    }
+
+    /// Wait until the local clock reads `local`
+    ///
+    /// See [`resolve_local`](Self::resolve_local) for how skipped and
+    /// repeated hours are handled.
+    pub async fn wait_until_local(&self, local: NaiveDateTime) {
+        self.provider.wait_until(self.resolve_local(local)).await;
+    }
+
+    /// Get a [`Sleep`] that completes when the local clock reads `local`
+    pub fn sleep_until_local(&self, local: NaiveDateTime) -> Sleep {
+        self.provider.sleep_until(self.resolve_local(local))
+    }
+
+    /// Find the transition that skipped `local` by stepping to the first valid local minute
+    fn end_of_gap(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let minute = local.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(local);
+        (1..=MAX_GAP_MINUTES)
+            .find_map(|step| self.tz.from_local_datetime(&(minute + Duration::minutes(step))).earliest())
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or_else(|| panic!("local time {local} is not followed by a valid time in {}", self.tz))
+    }
+}
+
+impl std::fmt::Debug for ZonedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZonedProvider")
+            .field("tz", &self.tz)
+            .field("now", &self.now())
+            .finish()
+    }
+}
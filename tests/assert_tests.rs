@@ -0,0 +1,104 @@
+use hourglass_rs::assert::{self, hours, minutes};
+use hourglass_rs::{assert_no_pending_timers, assert_now, assert_waited};
+use hourglass_rs::WaitMode;
+use chrono::{DateTime, Duration, Utc};
+
+mod common;
+use common::test_provider;
+
+#[tokio::test]
+async fn test_passing_assertions() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+
+    for _ in 0..3 {
+        provider.wait_labeled("accrual", hours(1)).await;
+    }
+    provider.wait_labeled("billing", minutes(30)).await;
+
+    assert_waited!(control, "accrual", times = 3, total = hours(3));
+    assert_waited!(control, "billing");
+    assert_waited!(control, "billing", total = minutes(30));
+    assert_waited!(control, times = 4);
+    assert_waited!(control, "settlement", times = 0);
+    assert_no_pending_timers!(control);
+    assert_now!(provider, "2024-01-01T03:30:00Z");
+    assert_now!(provider, "2024-01-01T03:30:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+#[tokio::test]
+async fn test_failure_shows_timeline() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+
+    provider.wait_labeled("accrual", hours(1)).await;
+    control.advance(minutes(90));
+    provider.wait(Duration::seconds(45)).await;
+    control.set("2024-01-01T00:00:00Z".parse().unwrap());
+
+    let message = assert::check_waited(&control, Some("accrual"), Some(3), Some(hours(3))).unwrap_err();
+    assert_eq!(
+        message,
+        "expected 3 waits labelled \"accrual\", found 1\n\
+         expected waits labelled \"accrual\" to total 3h, found 1h\n\
+         timeline (clock now at 2024-01-01T00:00:00Z):\n  \
+         2024-01-01T00:00:00Z  wait 1h \"accrual\" until 2024-01-01T01:00:00Z, done at 2024-01-01T01:00:00Z\n  \
+         2024-01-01T01:00:00Z  advance 1h30m to 2024-01-01T02:30:00Z\n  \
+         2024-01-01T02:30:00Z  wait 45s until 2024-01-01T02:30:45Z, done at 2024-01-01T02:30:45Z\n  \
+         2024-01-01T02:30:45Z  set to 2024-01-01T00:00:00Z, backwards"
+    );
+
+    let message = assert::check_now(&provider, "2024-01-01T00:00:01.5Z").unwrap_err();
+    assert!(message.starts_with(
+        "expected the clock to read 2024-01-01T00:00:01.500Z, but it reads 2024-01-01T00:00:00Z (1s500ms early)\ntimeline"
+    ));
+}
+
+#[tokio::test]
+async fn test_pending_timers_are_reported() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let p = provider.clone();
+    let handle = tokio::spawn(async move { p.wait_labeled("midnight", hours(24)).await });
+    while control.wait_call_count() < 1 {
+        tokio::task::yield_now().await;
+    }
+    tokio::task::yield_now().await;
+
+    let message = assert::check_no_pending_timers(&control).unwrap_err();
+    assert!(message.starts_with(
+        "expected no pending timers, found 1:\n  due 2024-01-02T00:00:00Z \"midnight\", created at 2024-01-01T00:00:00Z\n"
+    ));
+    assert!(message.ends_with("wait 1d \"midnight\" until 2024-01-02T00:00:00Z, pending"));
+
+    control.advance(hours(24));
+    handle.await.unwrap();
+    assert_no_pending_timers!(control);
+}
+
+#[test]
+#[should_panic(expected = "assert_now! failed: expected the clock to read 2024-01-02T00:00:00Z")]
+fn test_assert_now_panics() {
+    let provider = test_provider();
+    assert_now!(provider, "2024-01-02T00:00:00Z");
+}
+
+#[tokio::test]
+async fn test_wait_counts_survive_history_limit() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_history_limit(10);
+
+    for _ in 0..20 {
+        provider.wait_labeled("poll", minutes(1)).await;
+    }
+
+    assert_waited!(control, times = 20, total = minutes(20));
+    let message = assert::check_waited(&control, Some("poll"), Some(20), None).unwrap_err();
+    assert!(message.starts_with(
+        "expected 20 waits labelled \"poll\", found 10\n\
+         only the last 10 of 20 waits are still in the history\n"
+    ));
+}
@@ -0,0 +1,80 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::Duration;
+
+mod common;
+use common::{at, test_provider};
+
+#[test]
+fn test_system_sleep_blocking() {
+    let provider = SafeTimeProvider::new(TimeSource::System);
+    let start = std::time::Instant::now();
+    provider.sleep_blocking(Duration::milliseconds(50));
+    assert!(start.elapsed() >= std::time::Duration::from_millis(50));
+
+    provider.sleep_until_blocking(provider.now() + Duration::milliseconds(20));
+    assert!(start.elapsed() >= std::time::Duration::from_millis(65));
+}
+
+#[test]
+fn test_parked_thread_released_by_advance() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let p = provider.clone();
+    let worker = std::thread::spawn(move || {
+        p.sleep_blocking(Duration::hours(1));
+        p.now()
+    });
+    while control.pending_count() == 0 {
+        std::thread::yield_now();
+    }
+
+    control.advance(Duration::minutes(30));
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert!(!worker.is_finished());
+
+    control.advance(Duration::minutes(30));
+    assert_eq!(worker.join().unwrap(), at("2024-01-01T01:00:00Z"));
+    assert_eq!(control.wait_history()[0].completed_at, Some(at("2024-01-01T01:00:00Z")));
+    assert_eq!(control.pending_count(), 0);
+}
+
+#[test]
+fn test_blocking_follows_wait_mode() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+
+    provider.sleep_blocking(Duration::days(1));
+    assert_eq!(provider.now(), at("2024-01-02T00:00:00Z"));
+
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    let threads: Vec<_> = [3, 1, 2]
+        .into_iter()
+        .map(|hours| {
+            let p = provider.clone();
+            std::thread::spawn(move || {
+                p.sleep_until_blocking(at("2024-01-02T00:00:00Z") + Duration::hours(hours));
+                p.now()
+            })
+        })
+        .collect();
+    let woke_at: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    assert_eq!(woke_at[0], at("2024-01-02T03:00:00Z"));
+    assert!(woke_at[1] >= at("2024-01-02T01:00:00Z"));
+    assert_eq!(provider.now(), at("2024-01-02T03:00:00Z"));
+}
+
+#[test]
+fn test_auto_advance_blocking_sleep_pauses_between_polls() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+
+    let start = std::time::Instant::now();
+    provider.sleep_blocking(Duration::hours(1));
+    assert_eq!(provider.now(), at("2024-01-01T01:00:00Z"));
+    // The clock only counts as idle after several paused polls, not a busy loop
+    assert!(start.elapsed() >= std::time::Duration::from_millis(10));
+    assert_eq!(control.pending_count(), 0);
+}
@@ -0,0 +1,262 @@
+use hourglass_rs::calendar::{self, BusinessCalendar, DayCount, EndOfMonth, HolidayCalendar, Weekend};
+use hourglass_rs::{SafeTimeProvider, TimeSource};
+use chrono::{NaiveDate, Weekday};
+
+mod common;
+use common::at;
+
+fn date(date: &str) -> NaiveDate {
+    date.parse().unwrap()
+}
+
+#[test]
+fn test_add_months_clamps_to_month_end() {
+    assert_eq!(calendar::add_months(at("2024-01-31T09:30:00Z"), 1), at("2024-02-29T09:30:00Z"));
+    assert_eq!(calendar::add_months(at("2023-01-31T00:00:00Z"), 1), at("2023-02-28T00:00:00Z"));
+    assert_eq!(calendar::add_months(at("2024-03-31T00:00:00Z"), 1), at("2024-04-30T00:00:00Z"));
+    assert_eq!(calendar::add_months(at("2024-01-15T00:00:00Z"), 1), at("2024-02-15T00:00:00Z"));
+}
+
+#[test]
+fn test_add_months_across_years_and_backwards() {
+    assert_eq!(calendar::add_months(at("2024-11-30T00:00:00Z"), 3), at("2025-02-28T00:00:00Z"));
+    assert_eq!(calendar::add_months(at("2024-03-31T00:00:00Z"), -1), at("2024-02-29T00:00:00Z"));
+    assert_eq!(calendar::add_months(at("2024-01-15T00:00:00Z"), -13), at("2022-12-15T00:00:00Z"));
+    assert_eq!(calendar::add_months(at("2024-01-15T00:00:00Z"), 0), at("2024-01-15T00:00:00Z"));
+}
+
+#[test]
+fn test_end_of_month_conventions() {
+    let feb29 = at("2024-02-29T00:00:00Z");
+    let jan31 = at("2023-01-31T00:00:00Z");
+
+    assert_eq!(calendar::add_months_with(feb29, 1, EndOfMonth::Clamp), at("2024-03-29T00:00:00Z"));
+    assert_eq!(
+        calendar::add_months_with(feb29, 1, EndOfMonth::PreserveEndOfMonth),
+        at("2024-03-31T00:00:00Z")
+    );
+    assert_eq!(calendar::add_months_with(jan31, 1, EndOfMonth::RollForward), at("2023-03-01T00:00:00Z"));
+
+    // Preserving only applies to dates already on the last day
+    assert_eq!(
+        calendar::add_months_with(at("2024-02-28T00:00:00Z"), 1, EndOfMonth::PreserveEndOfMonth),
+        at("2024-03-28T00:00:00Z")
+    );
+}
+
+#[test]
+fn test_add_years_handles_leap_day() {
+    let leap_day = at("2024-02-29T12:00:00Z");
+    assert_eq!(calendar::add_years(leap_day, 1), at("2025-02-28T12:00:00Z"));
+    assert_eq!(calendar::add_years(leap_day, 4), at("2028-02-29T12:00:00Z"));
+    assert_eq!(calendar::add_years_with(leap_day, 1, EndOfMonth::RollForward), at("2025-03-01T12:00:00Z"));
+}
+
+#[test]
+fn test_month_helpers() {
+    assert_eq!(calendar::days_in_month(2024, 2), 29);
+    assert_eq!(calendar::days_in_month(1900, 2), 28);
+    assert_eq!(calendar::days_in_month(2000, 2), 29);
+    assert!(calendar::is_last_day_of_month(NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()));
+    assert!(!calendar::is_last_day_of_month(NaiveDate::from_ymd_opt(2024, 2, 28).unwrap()));
+    assert_eq!(
+        calendar::end_of_month(NaiveDate::from_ymd_opt(2024, 4, 10).unwrap()),
+        NaiveDate::from_ymd_opt(2024, 4, 30).unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid month 13")]
+fn test_days_in_month_rejects_invalid_month() {
+    calendar::days_in_month(2024, 13);
+}
+
+#[test]
+fn test_advance_months() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-31T08:00:00Z")));
+    let control = provider.test_control().unwrap();
+
+    control.advance_months(1);
+    assert_eq!(provider.now(), at("2024-02-29T08:00:00Z"));
+
+    // Clamping is not sticky, each step starts from the current day
+    control.advance_months(1);
+    assert_eq!(provider.now(), at("2024-03-29T08:00:00Z"));
+
+    control.advance_months(12);
+    assert_eq!(provider.now(), at("2025-03-29T08:00:00Z"));
+}
+
+#[test]
+#[should_panic(expected = "cannot advance more than i32::MAX months")]
+fn test_advance_months_rejects_count_beyond_i32() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-31T08:00:00Z")));
+    provider.test_control().unwrap().advance_months(u32::MAX);
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-12, "expected {expected}, got {actual}");
+}
+
+fn christmas_calendar() -> HolidayCalendar {
+    HolidayCalendar::parse(
+        "# Christmas\n\
+         2024-12-25 Christmas Day\n\
+         \n\
+         2024-12-26 Boxing Day\n",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_weekend_rules() {
+    let saturday = date("2024-06-01");
+    let friday = date("2024-05-31");
+
+    assert!(!Weekend::SATURDAY_SUNDAY.is_business_day(saturday));
+    assert!(Weekend::SATURDAY_SUNDAY.is_business_day(friday));
+    assert!(!Weekend::FRIDAY_SATURDAY.is_business_day(friday));
+    assert!(Weekend::NONE.is_business_day(saturday));
+    assert_eq!(Weekend::new(&[Weekday::Sat, Weekday::Sun]), Weekend::default());
+}
+
+#[test]
+#[should_panic(expected = "no business day within 366 days")]
+fn test_calendar_without_business_days_panics() {
+    let every_day = Weekend::new(&[
+        Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun,
+    ]);
+    every_day.next_business_day(at("2024-01-01T00:00:00Z"));
+}
+
+#[test]
+fn test_holiday_calendar_rolls_over_weekends_and_holidays() {
+    let calendar = christmas_calendar();
+    assert!(calendar.is_holiday(date("2024-12-25")));
+    assert!(!calendar.is_business_day(date("2024-12-26")));
+
+    // Tuesday 24th -> Friday 27th, skipping two holidays
+    assert_eq!(calendar.next_business_day(at("2024-12-24T10:00:00Z")), at("2024-12-27T10:00:00Z"));
+    assert_eq!(calendar.roll_forward(at("2024-12-25T10:00:00Z")), at("2024-12-27T10:00:00Z"));
+    assert_eq!(calendar.roll_forward(at("2024-12-24T10:00:00Z")), at("2024-12-24T10:00:00Z"));
+    assert_eq!(calendar.previous_business_day(at("2024-12-27T10:00:00Z")), at("2024-12-24T10:00:00Z"));
+
+    // Friday 27th + 2 business days -> Tuesday 31st
+    assert_eq!(calendar.add_business_days(at("2024-12-27T00:00:00Z"), 2), at("2024-12-31T00:00:00Z"));
+    assert_eq!(calendar.add_business_days(at("2024-12-31T00:00:00Z"), -2), at("2024-12-27T00:00:00Z"));
+    assert_eq!(calendar.add_business_days(at("2024-12-28T00:00:00Z"), 0), at("2024-12-28T00:00:00Z"));
+}
+
+#[test]
+fn test_holiday_calendar_parses_ics() {
+    let calendar = HolidayCalendar::parse(
+        "BEGIN:VCALENDAR\n\
+         VERSION:2.0\n\
+         BEGIN:VEVENT\n\
+         DTSTART;VALUE=DATE:20250101\n\
+         SUMMARY:New Year's Day\n\
+         END:VEVENT\n\
+         BEGIN:VEVENT\n\
+         DTSTART:20250526T000000Z\n\
+         SUMMARY:Memorial Day\n\
+         END:VEVENT\n\
+         END:VCALENDAR\n",
+    )
+    .unwrap();
+
+    assert_eq!(calendar.holidays().collect::<Vec<_>>(), vec![date("2025-01-01"), date("2025-05-26")]);
+}
+
+#[test]
+fn test_holiday_calendar_reports_bad_lines() {
+    let error = HolidayCalendar::parse("2024-01-01\n# ok\n01/02/2024 Bad\n").unwrap_err();
+    assert_eq!(error.line(), 3);
+    assert!(error.to_string().contains("01/02/2024"));
+}
+
+#[test]
+fn test_holiday_calendar_load_from_file() {
+    let path = std::env::temp_dir().join(format!("hourglass-holidays-{}.txt", std::process::id()));
+    std::fs::write(&path, "2024-07-04 Independence Day\n").unwrap();
+    let calendar = HolidayCalendar::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(calendar.is_holiday(date("2024-07-04")));
+    assert!(HolidayCalendar::load(&path).is_err());
+}
+
+#[test]
+fn test_advance_business_days() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-12-20T09:00:00Z"))); // Friday
+    let control = provider.test_control().unwrap();
+    let calendar = christmas_calendar();
+
+    control.advance_business_days(1, &calendar);
+    assert_eq!(provider.now(), at("2024-12-23T09:00:00Z"));
+
+    control.advance_business_days(2, &calendar);
+    assert_eq!(provider.now(), at("2024-12-27T09:00:00Z"));
+
+    control.advance_business_days(1, &Weekend::NONE);
+    assert_eq!(provider.now(), at("2024-12-28T09:00:00Z"));
+}
+
+#[test]
+#[should_panic(expected = "cannot advance more than i32::MAX business days")]
+fn test_advance_business_days_rejects_count_beyond_i32() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-12-20T09:00:00Z")));
+    let control = provider.test_control().unwrap();
+    control.advance_business_days(u32::MAX, &Weekend::NONE);
+}
+
+#[test]
+fn test_actual_day_counts_across_leap_year() {
+    let start = at("2024-01-01T00:00:00Z");
+    let end = at("2025-01-01T00:00:00Z");
+
+    assert_close(DayCount::Actual365Fixed.year_fraction(start, end), 366.0 / 365.0);
+    assert_close(DayCount::Actual360.year_fraction(start, end), 366.0 / 360.0);
+    assert_close(DayCount::ActualActualIsda.year_fraction(start, end), 1.0);
+
+    // Split across the year boundary: 184 days of 2023, 182 days of 2024
+    assert_close(
+        DayCount::ActualActualIsda.year_fraction(at("2023-07-01T00:00:00Z"), at("2024-07-01T00:00:00Z")),
+        184.0 / 365.0 + 182.0 / 366.0,
+    );
+    assert_eq!(DayCount::Actual365Fixed.day_count(start, end), 366);
+}
+
+#[test]
+fn test_thirty_360_month_ends() {
+    let jan31 = at("2024-01-31T00:00:00Z");
+    let feb29 = at("2024-02-29T00:00:00Z");
+    let mar31 = at("2024-03-31T00:00:00Z");
+
+    assert_eq!(DayCount::Thirty360Us.day_count(jan31, feb29), 29);
+    assert_eq!(DayCount::Thirty360Us.day_count(feb29, mar31), 30);
+    assert_eq!(DayCount::Thirty360Us.day_count(feb29, at("2025-02-28T00:00:00Z")), 360);
+    assert_eq!(DayCount::ThirtyE360.day_count(feb29, mar31), 31);
+    assert_eq!(DayCount::ThirtyE360.day_count(jan31, mar31), 60);
+    assert_close(DayCount::Thirty360Us.year_fraction(jan31, at("2024-07-31T00:00:00Z")), 0.5);
+}
+
+#[test]
+fn test_day_count_ignores_time_of_day_and_handles_reversed_periods() {
+    let start = at("2024-03-01T23:59:00Z");
+    let end = at("2024-03-31T00:01:00Z");
+
+    assert_eq!(DayCount::Actual360.day_count(start, end), 30);
+    assert_close(DayCount::Actual360.year_fraction(end, start), -30.0 / 360.0);
+    assert_eq!(DayCount::ThirtyE360.day_count(end, start), -29);
+    assert_close(DayCount::ActualActualIsda.year_fraction(start, start), 0.0);
+}
+
+#[tokio::test]
+async fn test_year_fraction_from_provider_clock() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-02-01T00:00:00Z")));
+    let start = provider.now();
+    provider.wait(chrono::Duration::days(29)).await;
+
+    assert_close(DayCount::Actual365Fixed.year_fraction(start, provider.now()), 29.0 / 365.0);
+    assert_close(DayCount::ActualActualIsda.year_fraction(start, provider.now()), 29.0 / 366.0);
+}
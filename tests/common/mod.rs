@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::{DateTime, Utc};
+
+/// Parse an RFC 3339 timestamp
+pub fn at(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+/// A test provider starting at 2024-01-01 00:00 UTC
+pub fn test_provider() -> SafeTimeProvider {
+    SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")))
+}
+
+/// A test provider starting at 2024-01-01 00:00 UTC whose waits park until
+/// the clock is moved
+pub fn parked_provider() -> SafeTimeProvider {
+    let provider = test_provider();
+    provider.test_control().unwrap().set_wait_mode(WaitMode::Parked);
+    provider
+}
@@ -1,5 +1,6 @@
-use hourglass_rs::{SafeTimeProvider, TimeSource};
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
 use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
 
 #[tokio::test]
 async fn test_safe_provider_with_system_has_no_control() {
@@ -188,4 +189,122 @@ async fn test_env_source_test_without_start() {
     unsafe {
         std::env::remove_var("TIME_SOURCE");
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_parked_wait_blocks_until_advance() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    
+    let p = provider.clone();
+    let handle = tokio::spawn(async move {
+        p.wait(Duration::hours(2)).await;
+        p.now()
+    });
+    
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+    
+    // Not far enough yet
+    control.advance(Duration::hours(1));
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+    
+    control.advance(Duration::hours(1));
+    let woke_at = handle.await.unwrap();
+    assert_eq!(woke_at, "2024-01-01T02:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    assert_eq!(control.total_waited(), Duration::hours(2));
+    assert_eq!(control.wait_call_count(), 1);
+}
+
+#[tokio::test]
+async fn test_parked_sleepers_wake_in_deadline_order() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut handles = vec![];
+    for hours in [3, 1, 2] {
+        let p = provider.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            p.wait(Duration::hours(hours)).await;
+            order.lock().unwrap().push(hours);
+        }));
+    }
+    
+    tokio::task::yield_now().await;
+    control.advance(Duration::hours(5));
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    
+    assert_eq!(*order.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_parked_wait_until_released_by_set() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    
+    let p = provider.clone();
+    let handle = tokio::spawn(async move {
+        p.wait_until("2024-01-01T12:00:00Z".parse().unwrap()).await;
+    });
+    
+    // Moving the clock backwards never releases a sleeper
+    tokio::task::yield_now().await;
+    control.set("2023-12-31T00:00:00Z".parse().unwrap());
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+    
+    control.set("2024-01-01T12:00:00Z".parse().unwrap());
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_instant_elapsed_survives_backwards_set() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    
+    let started = provider.instant();
+    control.advance(Duration::seconds(30));
+    control.set("2023-01-01T00:00:00Z".parse().unwrap());
+    
+    // Wall-clock difference is negative, monotonic elapsed is not
+    assert!(provider.now() < "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    assert_eq!(started.elapsed(&provider), Duration::seconds(30));
+}
+
+#[tokio::test]
+async fn test_forward_set_releases_parked_wait_without_moving_instant() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    
+    let p = provider.clone();
+    let handle = tokio::spawn(async move {
+        let started = p.instant();
+        p.wait(Duration::hours(1)).await;
+        started.elapsed(&p)
+    });
+    while control.pending_count() == 0 {
+        tokio::task::yield_now().await;
+    }
+    
+    control.set("2024-01-01T01:00:00Z".parse().unwrap());
+    assert_eq!(handle.await.unwrap(), Duration::zero());
+}
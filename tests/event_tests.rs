@@ -0,0 +1,116 @@
+use hourglass_rs::event::CLOCK_EVENT_CAPACITY;
+use hourglass_rs::{ClockEvent, SafeTimeProvider, TestTimeProvider, TimeSource};
+use chrono::Duration;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::at;
+
+#[tokio::test]
+async fn test_subscribe_reports_clock_changes() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+    let mut events = provider.subscribe();
+    assert_eq!(events.try_next_event(), None);
+
+    control.advance(Duration::hours(1));
+    control.set(at("2024-01-02T00:00:00Z"));
+    control.set(at("2023-12-31T00:00:00Z"));
+    // Waits move the clock without reporting it
+    provider.wait(Duration::minutes(5)).await;
+
+    assert_eq!(
+        events.next_event().await,
+        ClockEvent::Advanced { from: at("2024-01-01T00:00:00Z"), to: at("2024-01-01T01:00:00Z") }
+    );
+    assert_eq!(
+        events.next_event().await,
+        ClockEvent::Set { from: at("2024-01-01T01:00:00Z"), to: at("2024-01-02T00:00:00Z") }
+    );
+    let backwards = events.next_event().await;
+    assert_eq!(backwards, ClockEvent::WentBackwards { from: at("2024-01-02T00:00:00Z"), to: at("2023-12-31T00:00:00Z") });
+    assert_eq!(backwards.to() - backwards.from(), Duration::days(-2));
+    assert_eq!(events.try_next_event(), None);
+}
+
+#[tokio::test]
+async fn test_subscriber_wakes_on_jump() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+
+    let mut events = provider.subscribe();
+    let cache = tokio::spawn(async move { events.next_event().await });
+    tokio::task::yield_now().await;
+
+    control.advance(Duration::days(1));
+    assert_eq!(cache.await.unwrap().to(), at("2024-01-02T00:00:00Z"));
+}
+
+#[test]
+fn test_callbacks_on_test_provider() {
+    let provider = TestTimeProvider::new(at("2024-01-01T00:00:00Z"));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let sink = seen.clone();
+    let id = provider.on_clock_change(move |event| sink.lock().unwrap().push(*event));
+    provider.advance(Duration::minutes(-1));
+    assert!(provider.remove_observer(id));
+    assert!(!provider.remove_observer(id));
+    provider.advance(Duration::minutes(1));
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![ClockEvent::WentBackwards { from: at("2024-01-01T00:00:00Z"), to: at("2023-12-31T23:59:00Z") }]
+    );
+}
+
+#[test]
+fn test_production_stream_is_silent() {
+    let provider = SafeTimeProvider::new(TimeSource::System);
+    let mut events = provider.subscribe();
+    assert_eq!(events.try_next_event(), None);
+}
+
+#[test]
+fn test_events_from_many_threads_arrive_in_clock_order() {
+    let provider = Arc::new(TestTimeProvider::new(at("2024-01-01T00:00:00Z")));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    provider.on_clock_change(move |event| sink.lock().unwrap().push(*event));
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let provider = provider.clone();
+            std::thread::spawn(move || {
+                for _ in 0..250 {
+                    provider.advance(Duration::seconds(1));
+                }
+            })
+        })
+        .collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1000);
+    assert!(seen.windows(2).all(|pair| pair[0].to() == pair[1].from()));
+    assert_eq!(seen.last().unwrap().to(), at("2024-01-01T00:16:40Z"));
+}
+
+#[test]
+fn test_unread_events_keep_only_the_latest() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+    let mut events = provider.subscribe();
+
+    for _ in 0..CLOCK_EVENT_CAPACITY + 10 {
+        control.advance(Duration::seconds(1));
+    }
+
+    let first = events.try_next_event().unwrap();
+    assert_eq!(first.from(), at("2024-01-01T00:00:10Z"));
+    let mut count = 1;
+    while events.try_next_event().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, CLOCK_EVENT_CAPACITY);
+}
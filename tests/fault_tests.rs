@@ -0,0 +1,158 @@
+use hourglass_rs::ClockFaults;
+use chrono::Duration;
+
+mod common;
+use common::{at, test_provider};
+
+#[test]
+fn test_skew_and_drift() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let skewed = provider.with_faults(ClockFaults::new().with_skew(Duration::seconds(-3)));
+    let drifting = provider.with_faults(ClockFaults::new().with_drift_ppm(100.0));
+
+    assert_eq!(skewed.now(), at("2023-12-31T23:59:57Z"));
+    assert_eq!(drifting.now(), at("2024-01-01T00:00:00Z"));
+
+    // 100ppm over a day is 8.64s
+    control.advance(Duration::days(1));
+    assert_eq!(skewed.now(), at("2024-01-01T23:59:57Z"));
+    assert_eq!(drifting.now(), at("2024-01-02T00:00:08.64Z"));
+    assert_eq!(provider.now(), at("2024-01-02T00:00:00Z"));
+}
+
+#[test]
+fn test_seeded_jitter_is_reproducible() {
+    let provider = test_provider();
+    let faults = ClockFaults::new().with_jitter(Duration::milliseconds(50)).with_seed(7);
+    let first = provider.with_faults(faults.clone());
+    let second = provider.with_faults(faults);
+    let other_seed = provider.with_faults(ClockFaults::new().with_jitter(Duration::milliseconds(50)).with_seed(8));
+
+    let readings: Vec<_> = (0..20).map(|_| first.now()).collect();
+    assert_eq!(readings, (0..20).map(|_| second.now()).collect::<Vec<_>>());
+    assert_ne!(readings, (0..20).map(|_| other_seed.now()).collect::<Vec<_>>());
+
+    let true_now = provider.now();
+    assert!(readings.iter().all(|reading| (*reading - true_now).abs() <= Duration::milliseconds(50)));
+    assert!(readings.iter().any(|reading| *reading != true_now));
+}
+
+#[test]
+fn test_jitter_with_huge_bound_stays_in_range() {
+    let provider = test_provider();
+    let faulty = provider.with_faults(ClockFaults::new().with_jitter(Duration::MAX).with_seed(3));
+
+    let true_now = provider.now();
+    for _ in 0..100 {
+        // Capped at the nanosecond range rather than overflowing
+        assert!((faulty.now() - true_now).abs() <= Duration::nanoseconds(i64::MAX));
+    }
+}
+
+#[test]
+fn test_scheduled_backward_jump() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let faulty = provider.with_faults(
+        ClockFaults::new()
+            .with_skew(Duration::seconds(30))
+            .with_jump_at(at("2024-01-01T01:00:00Z"), Duration::seconds(-60)),
+    );
+
+    control.advance(Duration::seconds(59 * 60 + 50));
+    let before = faulty.now();
+    let start = faulty.instant();
+    assert_eq!(before, at("2024-01-01T01:00:20Z"));
+
+    // The NTP correction lands and the clock reads earlier than before
+    control.advance(Duration::seconds(10));
+    let after = faulty.now();
+    assert_eq!(after, at("2024-01-01T00:59:30Z"));
+    assert!(after < before);
+    assert_eq!(faulty.instant() - start, Duration::seconds(10));
+}
+
+#[tokio::test]
+async fn test_waits_use_faulty_deadlines() {
+    let provider = test_provider();
+    let faulty = provider.with_faults(ClockFaults::new().with_skew(Duration::hours(1)));
+    assert!(faulty.is_test_mode());
+
+    faulty.wait_until(at("2024-01-01T01:10:00Z")).await;
+    assert_eq!(provider.now(), at("2024-01-01T00:10:00Z"));
+    assert_eq!(faulty.now(), at("2024-01-01T01:10:00Z"));
+
+    faulty.wait(Duration::minutes(5)).await;
+    assert_eq!(provider.now(), at("2024-01-01T00:15:00Z"));
+    assert_eq!(faulty.test_control().unwrap().wait_call_count(), 2);
+}
+
+#[tokio::test]
+async fn test_timeout_on_drifting_clock() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    // Runs 10% fast, so its hour passes after about 54.5 true minutes
+    let fast = provider.with_faults(ClockFaults::new().with_drift_ppm(100_000.0));
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let f = fast.clone();
+    let handle = tokio::spawn(async move { f.timeout(Duration::hours(1), rx).await });
+    tokio::task::yield_now().await;
+
+    control.advance(Duration::minutes(54));
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+
+    control.advance(Duration::minutes(1));
+    let elapsed = handle.await.unwrap().unwrap_err();
+    assert_eq!(elapsed.deadline(), at("2024-01-01T01:00:00Z"));
+}
+
+#[tokio::test]
+async fn test_sleep_deadlines_read_on_faulty_clock() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let skewed = provider.with_faults(ClockFaults::new().with_skew(Duration::minutes(5)));
+
+    let sleep = skewed.sleep_until(at("2024-01-01T01:05:00Z"));
+    assert_eq!(sleep.deadline(), at("2024-01-01T01:05:00Z"));
+    assert_eq!(skewed.sleep(Duration::hours(1)).deadline(), at("2024-01-01T01:05:00Z"));
+
+    // Resetting converts the new deadline as well
+    let mut sleep = std::pin::pin!(sleep);
+    sleep.as_mut().reset(at("2024-01-01T00:35:00Z"));
+    assert_eq!(sleep.deadline(), at("2024-01-01T00:35:00Z"));
+    control.advance(Duration::minutes(30));
+    assert!(sleep.is_elapsed());
+
+    let result = skewed.timeout_at(at("2024-01-01T00:40:00Z"), std::future::pending::<()>());
+    control.advance(Duration::minutes(5));
+    assert_eq!(result.await.unwrap_err().deadline(), at("2024-01-01T00:40:00Z"));
+}
+
+#[tokio::test]
+async fn test_interval_on_skewed_clock() {
+    let provider = test_provider();
+    let skewed = provider.with_faults(ClockFaults::new().with_skew(Duration::minutes(5)));
+
+    let mut interval = skewed.interval(Duration::hours(1));
+    assert_eq!(interval.tick().await, at("2024-01-01T00:05:00Z"));
+    assert_eq!(interval.tick().await, at("2024-01-01T01:05:00Z"));
+    assert_eq!(interval.tick().await, at("2024-01-01T02:05:00Z"));
+    assert_eq!(provider.now(), at("2024-01-01T02:00:00Z"));
+}
+
+#[tokio::test]
+async fn test_interval_on_drifting_clock() {
+    let provider = test_provider();
+    // Runs 10% fast, so each of its hours passes after about 54.5 true minutes
+    let fast = provider.with_faults(ClockFaults::new().with_drift_ppm(100_000.0));
+
+    let mut interval = fast.interval(Duration::hours(1));
+    for hour in 0..3 {
+        assert_eq!(interval.tick().await, at("2024-01-01T00:00:00Z") + Duration::hours(hour));
+    }
+    assert!(provider.now() < at("2024-01-01T01:50:00Z"));
+    assert!(provider.now() > at("2024-01-01T01:49:00Z"));
+}
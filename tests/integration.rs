@@ -1,4 +1,4 @@
-use hourglass_rs::{SafeTimeProvider, TimeSource};
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
 use chrono::{Duration, DateTime, Utc};
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -64,7 +64,7 @@ async fn test_service_with_real_time() {
     let count = service.get_execution_count().await;
     
     // Should have executed approximately 5 times (50ms / 10ms)
-    assert!(count >= 4 && count <= 6); // Allow some margin for timing
+    assert!((4..=6).contains(&count)); // Allow some margin for timing
 }
 
 #[tokio::test]
@@ -74,6 +74,7 @@ async fn test_service_with_test_time() {
         TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
     );
     let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
     
     let service = Arc::new(ScheduledService::new(provider.clone()));
     
@@ -83,13 +84,12 @@ async fn test_service_with_test_time() {
         service_clone.run_every_hour(Duration::hours(24)).await;
     });
     
-    // Let the service start
-    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-    
-    // Simulate 24 hours passing instantly
+    // Simulate 24 hours passing instantly, stepping once the service is waiting
     for _hour in 0..24 {
+        while control.pending_count() == 0 {
+            tokio::task::yield_now().await;
+        }
         control.advance(Duration::hours(1));
-        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
     }
     
     // Wait for service to complete
@@ -113,6 +113,31 @@ async fn test_service_with_test_time() {
     assert_eq!(control.wait_call_count(), 24);
 }
 
+#[tokio::test]
+async fn test_service_stepped_with_run_until() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    
+    let service = Arc::new(ScheduledService::new(provider.clone()));
+    let service_clone = service.clone();
+    let handle = tokio::spawn(async move {
+        service_clone.run_every_hour(Duration::hours(24)).await;
+    });
+    
+    // Step through each hourly deadline without real sleeps
+    control.run_until("2024-01-01T12:00:00Z".parse().unwrap()).await;
+    assert_eq!(service.get_execution_count().await, 13);
+    assert!(!handle.is_finished());
+    
+    control.run_until("2024-01-02T00:00:00Z".parse().unwrap()).await;
+    handle.await.unwrap();
+    assert_eq!(service.get_execution_count().await, 24);
+    assert_eq!(control.pending_count(), 0);
+}
+
 #[tokio::test]
 async fn test_concurrent_services_with_test_time() {
     // Create three independent time providers
@@ -159,10 +184,6 @@ async fn test_concurrent_services_with_test_time() {
         }
     });
     
-    // Let services start and run
-    // Each service has its own isolated time, so they'll complete independently
-    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-    
     // Wait for all services to complete
     // Since each has isolated time, they'll advance through their schedules independently
     tokio::try_join!(handle1, handle2, handle3).unwrap();
@@ -179,6 +200,7 @@ async fn test_wait_until_with_concurrent_time_changes() {
         TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
     );
     let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
     
     // Task waiting until a specific time
     let provider_clone = provider.clone();
@@ -189,11 +211,14 @@ async fn test_wait_until_with_concurrent_time_changes() {
     });
     
     // Let the wait start
-    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    while control.pending_count() == 0 {
+        tokio::task::yield_now().await;
+    }
     
     // Advance time gradually
     control.advance(Duration::hours(6));
-    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    tokio::task::yield_now().await;
+    assert!(!wait_handle.is_finished());
     
     // Jump directly to target
     control.set("2024-01-01T12:00:00Z".parse().unwrap());
@@ -223,4 +248,142 @@ async fn test_mixed_production_and_test_usage() {
     
     // The exact same code would work in production with SystemTimeProvider
     // but would actually wait 8 hours
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_service_with_auto_advance() {
+    // No manual stepping: the clock jumps to the next deadline whenever the service is idle
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
+    let service = Arc::new(ScheduledService::new(provider.clone()));
+    let service_clone = service.clone();
+    tokio::spawn(async move {
+        service_clone.run_every_hour(Duration::hours(24)).await;
+    })
+    .await
+    .unwrap();
+    
+    let executions = service.get_executions().await;
+    assert_eq!(executions.len(), 24);
+    for (i, execution) in executions.iter().enumerate() {
+        let expected = "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+            + Duration::hours(i as i64);
+        assert_eq!(*execution, expected);
+    }
+    assert_eq!(provider.now(), "2024-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+#[tokio::test]
+async fn test_auto_advance_interleaves_services_by_deadline() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut handles = vec![];
+    for (name, every) in [("fast", 2), ("slow", 3)] {
+        let p = provider.clone();
+        let log = log.clone();
+        handles.push(tokio::spawn(async move {
+            for _ in 0..3 {
+                p.wait(Duration::hours(every)).await;
+                log.lock().await.push((name, p.now()));
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    
+    let at = |hour: i64| "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::hours(hour);
+    assert_eq!(
+        *log.lock().await,
+        vec![
+            ("fast", at(2)),
+            ("slow", at(3)),
+            ("fast", at(4)),
+            // Both due at 06:00; slow registered its timer first
+            ("slow", at(6)),
+            ("fast", at(6)),
+            ("slow", at(9)),
+        ]
+    );
+}
+
+// Other backends cannot yield behind tokio's own deferred yields
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_auto_advance_waits_for_busy_tasks() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
+    // A timer far in the future must not fire while another task keeps waiting on the clock
+    let p = provider.clone();
+    let sleeper = tokio::spawn(async move {
+        p.wait(Duration::days(1)).await;
+    });
+    for _ in 0..100 {
+        provider.wait(Duration::zero()).await;
+        tokio::task::yield_now().await;
+    }
+    assert!(!sleeper.is_finished());
+    assert_eq!(provider.now(), "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    
+    sleeper.await.unwrap();
+    assert_eq!(provider.now(), "2024-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+#[tokio::test]
+async fn test_auto_advance_ignores_clock_reads() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
+    // A task polling the clock does not hold the sleeper back
+    let p = provider.clone();
+    let sleeper = tokio::spawn(async move {
+        p.wait(Duration::days(1)).await;
+    });
+    let target = "2024-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    while provider.now() < target {
+        tokio::task::yield_now().await;
+    }
+    sleeper.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_auto_advance_does_not_wait_for_blocked_tasks() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    
+    // A task waiting on another thread is invisible to the clock, so the
+    // sleeper fires before the reply arrives
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        tx.send(()).unwrap();
+    });
+    let p = provider.clone();
+    let worker = tokio::spawn(async move {
+        rx.await.unwrap();
+        p.now()
+    });
+    
+    provider.wait(Duration::days(1)).await;
+    assert!(!worker.is_finished());
+    assert_eq!(worker.await.unwrap(), "2024-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+}
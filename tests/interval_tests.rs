@@ -0,0 +1,138 @@
+use hourglass_rs::{Interval, MissedTickBehavior, SafeTimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
+use std::task::Poll;
+
+mod common;
+use common::{at, parked_provider};
+
+/// Collect every tick that is ready without moving the clock
+async fn ready_ticks(interval: &mut Interval) -> Vec<DateTime<Utc>> {
+    let mut ticks = Vec::new();
+    loop {
+        let poll = std::future::poll_fn(|cx| Poll::Ready(interval.poll_tick(cx))).await;
+        match poll {
+            Poll::Ready(tick) => ticks.push(tick),
+            Poll::Pending => return ticks,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_interval_does_not_drift_with_work() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let mut interval = provider.interval(Duration::hours(1));
+
+    let mut ticks = Vec::new();
+    for _ in 0..4 {
+        ticks.push(interval.tick().await);
+        // Work that takes time should not push the schedule back
+        provider.wait(Duration::minutes(10)).await;
+    }
+
+    assert_eq!(
+        ticks,
+        vec![
+            at("2024-01-01T00:00:00Z"),
+            at("2024-01-01T01:00:00Z"),
+            at("2024-01-01T02:00:00Z"),
+            at("2024-01-01T03:00:00Z"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_interval_burst_after_advance() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let mut interval = provider.interval(Duration::hours(1));
+
+    assert_eq!(interval.tick().await, at("2024-01-01T00:00:00Z"));
+    assert!(ready_ticks(&mut interval).await.is_empty());
+
+    control.advance(Duration::hours(5));
+    let ticks = ready_ticks(&mut interval).await;
+    assert_eq!(ticks.len(), 5);
+    assert_eq!(ticks.first(), Some(&at("2024-01-01T01:00:00Z")));
+    assert_eq!(ticks.last(), Some(&at("2024-01-01T05:00:00Z")));
+}
+
+#[tokio::test]
+async fn test_interval_delay_after_advance() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let mut interval = provider.interval(Duration::hours(1));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval.tick().await;
+
+    control.advance(Duration::minutes(330)); // 05:30
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T01:00:00Z")]);
+
+    // The schedule restarts from the late tick
+    control.advance(Duration::minutes(59));
+    assert!(ready_ticks(&mut interval).await.is_empty());
+    control.advance(Duration::minutes(1));
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T06:30:00Z")]);
+}
+
+#[tokio::test]
+async fn test_interval_skip_after_advance() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let mut interval = provider.interval(Duration::hours(1));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval.tick().await;
+
+    control.advance(Duration::minutes(330)); // 05:30
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T01:00:00Z")]);
+
+    // Resumes on the original hourly grid
+    control.advance(Duration::minutes(30));
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T06:00:00Z")]);
+}
+
+#[tokio::test]
+async fn test_interval_skip_with_period_under_real_clock_slack() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let mut interval = provider.interval(Duration::milliseconds(1));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval.tick().await;
+
+    // The virtual clock has no jitter, so even a few milliseconds late is a miss
+    control.advance(Duration::milliseconds(4));
+    assert_eq!(
+        ready_ticks(&mut interval).await,
+        vec![at("2024-01-01T00:00:00.001Z")]
+    );
+    control.advance(Duration::milliseconds(1));
+    assert_eq!(
+        ready_ticks(&mut interval).await,
+        vec![at("2024-01-01T00:00:00.005Z")]
+    );
+}
+
+#[tokio::test]
+async fn test_interval_at_and_reset() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let mut interval = provider.interval_at(at("2024-01-01T02:00:00Z"), Duration::hours(1));
+
+    control.advance(Duration::hours(1));
+    assert!(ready_ticks(&mut interval).await.is_empty());
+    control.advance(Duration::hours(1));
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T02:00:00Z")]);
+
+    control.advance(Duration::minutes(15));
+    interval.reset();
+    control.advance(Duration::minutes(45));
+    assert!(ready_ticks(&mut interval).await.is_empty());
+    control.advance(Duration::minutes(15));
+    assert_eq!(ready_ticks(&mut interval).await, vec![at("2024-01-01T03:15:00Z")]);
+}
+
+#[tokio::test]
+#[should_panic(expected = "interval period must be positive")]
+async fn test_interval_rejects_zero_period() {
+    let provider = SafeTimeProvider::new(TimeSource::TestNow);
+    provider.interval(Duration::zero());
+}
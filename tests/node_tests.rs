@@ -0,0 +1,99 @@
+use hourglass_rs::WaitMode;
+use chrono::Duration;
+
+mod common;
+use common::{at, test_provider};
+
+#[test]
+fn test_nodes_follow_parent_with_own_skew() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let a = control.fork_node("node-a");
+    let b = control.fork_node("node-b");
+    assert_eq!(a.name(), "node-a");
+    assert_eq!(a.now(), provider.now());
+
+    b.skew(Duration::seconds(3));
+    control.advance(Duration::minutes(1));
+    assert_eq!(a.now(), at("2024-01-01T00:01:00Z"));
+    assert_eq!(b.now(), at("2024-01-01T00:01:03Z"));
+    assert_eq!(b.provider().now(), b.now());
+
+    // 1000ppm over 1000s is one second
+    a.set_drift_ppm(1000.0);
+    control.advance(Duration::seconds(1000));
+    assert_eq!(a.offset(), Duration::seconds(1));
+
+    // Changing the drift keeps what has accumulated
+    a.set_drift_ppm(0.0);
+    control.advance(Duration::hours(1));
+    assert_eq!(a.offset(), Duration::seconds(1));
+    a.set_offset(Duration::zero());
+    assert_eq!(a.now(), provider.now());
+    assert_eq!(b.offset(), Duration::seconds(3));
+}
+
+#[tokio::test]
+async fn test_lease_expires_early_on_fast_node() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let leader = control.fork_node("leader").provider();
+    let follower = control.fork_node("follower");
+    follower.skew(Duration::seconds(3));
+
+    // The leader grants a 10s lease by its own clock
+    let lease_expiry = leader.now() + Duration::seconds(10);
+    let follower_time = follower.provider();
+    assert!(follower_time.is_test_mode());
+    follower_time.wait_until_labeled("lease", lease_expiry).await;
+
+    // The follower takes over while the leader still holds the lease
+    assert_eq!(provider.now(), at("2024-01-01T00:00:07Z"));
+    assert!(leader.now() < lease_expiry);
+    assert_eq!(control.waits_for("lease").len(), 1);
+}
+
+#[tokio::test]
+async fn test_node_timers_fire_on_node_time() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    let slow = control.fork_node("slow");
+    slow.skew(Duration::minutes(-5));
+
+    let node_time = slow.provider();
+    let handle = tokio::spawn(async move {
+        node_time.wait_until(at("2024-01-01T01:00:00Z")).await;
+        node_time.now()
+    });
+    while control.pending_count() < 1 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(control.next_deadline(), Some(at("2024-01-01T01:05:00Z")));
+
+    control.run_until(at("2024-01-01T01:05:00Z")).await;
+    assert_eq!(handle.await.unwrap(), at("2024-01-01T01:00:00Z"));
+}
+
+#[tokio::test]
+async fn test_node_interval_ticks_on_node_time() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let slow = control.fork_node("slow");
+    slow.skew(Duration::minutes(-5));
+    let node_time = slow.provider();
+
+    let sleep = node_time.sleep_until(at("2024-01-01T01:00:00Z"));
+    assert_eq!(sleep.deadline(), at("2024-01-01T01:00:00Z"));
+    assert_eq!(node_time.sleep(Duration::hours(1)).deadline(), at("2024-01-01T00:55:00Z"));
+
+    let mut interval = node_time.interval(Duration::hours(1));
+    assert_eq!(interval.tick().await, at("2023-12-31T23:55:00Z"));
+    assert_eq!(interval.tick().await, at("2024-01-01T00:55:00Z"));
+    assert_eq!(interval.tick().await, at("2024-01-01T01:55:00Z"));
+    assert_eq!(provider.now(), at("2024-01-01T02:00:00Z"));
+
+    let result = node_time.timeout(Duration::minutes(10), std::future::pending::<()>());
+    control.advance(Duration::minutes(10));
+    assert_eq!(result.await.unwrap_err().deadline(), at("2024-01-01T02:05:00Z"));
+}
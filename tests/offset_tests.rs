@@ -0,0 +1,77 @@
+use hourglass_rs::{OffsetTimeProvider, SafeTimeProvider, TimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
+
+fn assert_close(actual: DateTime<Utc>, expected: DateTime<Utc>) {
+    assert!((actual - expected).abs() < Duration::seconds(1), "{} is not close to {}", actual, expected);
+}
+
+#[tokio::test]
+async fn test_offset_flows_in_real_time() {
+    let provider = SafeTimeProvider::new(TimeSource::Offset(Duration::days(-90)));
+    assert!(!provider.is_test_mode());
+    assert!(provider.test_control().is_none());
+    assert_close(provider.now(), Utc::now() - Duration::days(90));
+
+    let before = provider.now();
+    let start = std::time::Instant::now();
+    provider.wait(Duration::milliseconds(50)).await;
+    assert!(start.elapsed() >= std::time::Duration::from_millis(50));
+    assert!(provider.now() - before >= Duration::milliseconds(50));
+}
+
+#[tokio::test]
+async fn test_anchored_starts_at_anchor() {
+    let quarter_end: DateTime<Utc> = "2024-03-31T23:59:00Z".parse().unwrap();
+    let provider = SafeTimeProvider::new(TimeSource::Anchored(quarter_end));
+    assert_close(provider.now(), quarter_end);
+
+    // Deadlines are on the shifted clock
+    let start = std::time::Instant::now();
+    provider.wait_until(provider.now() + Duration::milliseconds(50)).await;
+    assert!(start.elapsed() >= std::time::Duration::from_millis(40));
+    assert_close(provider.now(), quarter_end);
+
+    let anchored = OffsetTimeProvider::anchored(quarter_end);
+    assert_close(Utc::now() + anchored.offset(), quarter_end);
+    assert!(!anchored.is_test());
+}
+
+#[test]
+fn test_offset_from_env() {
+    let cases = [
+        ("-P90D", Some(Duration::days(-90))),
+        ("P1W", Some(Duration::weeks(1))),
+        ("PT1H30M", Some(Duration::minutes(90))),
+        ("-P1DT0.5S", Some(-(Duration::days(1) + Duration::milliseconds(500)))),
+        ("P1Y", None),
+        ("P", None),
+        ("PT", None),
+        ("90D", None),
+    ];
+    for (value, expected) in cases {
+        unsafe {
+            std::env::set_var("TIME_SOURCE", "offset");
+            std::env::set_var("TIME_OFFSET", value);
+        }
+        match (TimeSource::from_env(), expected) {
+            (TimeSource::Offset(offset), Some(expected)) => assert_eq!(offset, expected, "{}", value),
+            (TimeSource::System, None) => {}
+            (source, _) => panic!("unexpected {:?} for {}", source, value),
+        }
+    }
+
+    unsafe {
+        std::env::set_var("TIME_SOURCE", "anchored");
+        std::env::set_var("TIME_START", "2024-03-31T00:00:00Z");
+    }
+    assert!(matches!(
+        TimeSource::from_env(),
+        TimeSource::Anchored(at) if at == "2024-03-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+    ));
+
+    unsafe {
+        std::env::remove_var("TIME_SOURCE");
+        std::env::remove_var("TIME_OFFSET");
+        std::env::remove_var("TIME_START");
+    }
+}
@@ -0,0 +1,64 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::Duration;
+
+mod common;
+use common::at;
+
+fn test_provider() -> SafeTimeProvider {
+    SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T18:00:00Z")))
+}
+
+#[tokio::test]
+async fn test_pending_timers_in_deadline_order() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    assert_eq!(control.pending_count(), 0);
+    assert_eq!(control.next_deadline(), None);
+
+    let p = provider.clone();
+    let nightly = tokio::spawn(async move { p.wait_until_labeled("nightly", at("2024-01-02T00:00:00Z")).await });
+    let p = provider.clone();
+    let poller = tokio::spawn(async move { p.wait(Duration::minutes(5)).await });
+    while control.pending_count() < 2 {
+        tokio::task::yield_now().await;
+    }
+
+    // Exactly one job is scheduled for midnight
+    let timers = control.pending_timers();
+    assert_eq!(timers.len(), 2);
+    assert_eq!(timers[0].deadline, at("2024-01-01T18:05:00Z"));
+    assert_eq!(timers[0].label, None);
+    assert_eq!(timers[1].deadline, at("2024-01-02T00:00:00Z"));
+    assert_eq!(timers[1].label.as_deref(), Some("nightly"));
+    assert_eq!(timers[1].created_at, at("2024-01-01T18:00:00Z"));
+    assert_eq!(control.next_deadline(), Some(at("2024-01-01T18:05:00Z")));
+
+    control.advance(Duration::minutes(5));
+    poller.await.unwrap();
+    assert_eq!(control.pending_count(), 1);
+    assert_eq!(control.next_deadline(), Some(at("2024-01-02T00:00:00Z")));
+
+    control.set(at("2024-01-02T00:00:00Z"));
+    nightly.await.unwrap();
+    assert!(control.pending_timers().is_empty());
+}
+
+#[tokio::test]
+async fn test_timeouts_are_pending_in_immediate_mode() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let p = provider.clone();
+    let handle = tokio::spawn(async move { p.timeout(Duration::hours(1), rx).await });
+    while control.pending_count() < 1 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(control.next_deadline(), Some(at("2024-01-01T19:00:00Z")));
+
+    // A dropped sleeper no longer counts
+    tx.send(()).unwrap();
+    assert!(handle.await.unwrap().is_ok());
+    assert_eq!(control.pending_count(), 0);
+}
@@ -184,4 +184,79 @@ async fn test_thread_safety() {
     
     // Should have recorded all wait calls
     assert_eq!(provider.wait_call_count(), 100); // 10 threads * 10 waits each
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_system_provider_instant_is_monotonic() {
+    let provider = SystemTimeProvider;
+    let first = provider.instant();
+    provider.wait(Duration::milliseconds(20)).await;
+    let second = provider.instant();
+    
+    assert!(second > first);
+    assert!(second.duration_since(first) >= Duration::milliseconds(20));
+    assert_eq!(first.duration_since(second), Duration::zero());
+    assert!(first.checked_duration_since(second).is_none());
+}
+
+#[tokio::test]
+async fn test_test_provider_instant_ignores_set() {
+    let provider = TestTimeProvider::new("2024-01-01T00:00:00Z".parse().unwrap());
+    let start = provider.instant();
+    
+    provider.advance(Duration::hours(2));
+    provider.wait(Duration::minutes(30)).await;
+    assert_eq!(provider.instant() - start, Duration::minutes(150));
+    
+    // Jumping the wall clock in either direction leaves the monotonic clock alone
+    provider.set("2023-06-01T00:00:00Z".parse().unwrap());
+    provider.set("2030-06-01T00:00:00Z".parse().unwrap());
+    provider.advance(Duration::hours(-1));
+    assert_eq!(provider.instant() - start, Duration::minutes(150));
+}
+
+#[tokio::test]
+async fn test_instant_arithmetic() {
+    let provider = TestTimeProvider::new("2024-01-01T00:00:00Z".parse().unwrap());
+    let start = provider.instant();
+    let later = start + Duration::seconds(90);
+    
+    assert_eq!(later - start, Duration::seconds(90));
+    assert_eq!(later - Duration::seconds(90), start);
+    assert_eq!(later + Duration::seconds(-30), start + Duration::minutes(1));
+    assert!(start.checked_sub(Duration::days(1)).is_none());
+    
+    let mut moving = start;
+    moving += Duration::minutes(2);
+    moving -= Duration::minutes(1);
+    assert_eq!(moving.duration_since(start), Duration::minutes(1));
+}
+
+/// A provider written against the original trait, with only the required methods
+struct FixedProvider(DateTime<Utc>);
+
+#[async_trait::async_trait]
+impl TimeProvider for FixedProvider {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+    
+    async fn wait(&self, _duration: Duration) {}
+    
+    async fn wait_until(&self, _deadline: DateTime<Utc>) {}
+    
+    fn is_test(&self) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn test_custom_provider_gets_default_sleep_and_instant() {
+    let provider = FixedProvider(Utc::now());
+    let start = provider.instant();
+    
+    let sleep = provider.sleep(Duration::milliseconds(20));
+    assert_eq!(sleep.deadline(), provider.0 + Duration::milliseconds(20));
+    sleep.await;
+    assert!(provider.instant() - start >= Duration::milliseconds(20));
+}
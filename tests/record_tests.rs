@@ -0,0 +1,166 @@
+use hourglass_rs::trace::{self, TraceEvent};
+use hourglass_rs::{RecordingTimeProvider, SafeTimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// In-memory trace sink that can be read back while the provider is alive
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn events(&self) -> Vec<TraceEvent> {
+        trace::read_trace(&self.0.lock().unwrap()[..]).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_records_reads_and_waits_with_call_site() {
+    let buffer = SharedBuffer::default();
+    let provider = SafeTimeProvider::new_from_recorder(Arc::new(RecordingTimeProvider::new(buffer.clone())));
+    assert!(!provider.is_test_mode());
+
+    let now = provider.now();
+    provider.wait(Duration::milliseconds(10)).await;
+    let deadline = provider.now() + Duration::milliseconds(10);
+    provider.wait_until(deadline).await;
+
+    let events = buffer.events();
+    assert_eq!(
+        events.iter().map(TraceEvent::kind).collect::<Vec<_>>(),
+        vec!["now", "wait", "now", "wait_until"]
+    );
+    assert!(events.iter().all(|event| event.label().starts_with("tests/record_tests.rs:")));
+
+    assert!(matches!(&events[0], TraceEvent::Now { value, .. } if *value == now));
+    match &events[1] {
+        TraceEvent::Wait { started, duration, completed, .. } => {
+            assert_eq!(*duration, Duration::milliseconds(10));
+            assert!(*completed - *started >= Duration::milliseconds(10));
+        }
+        other => panic!("expected a wait, got {:?}", other),
+    }
+    match &events[3] {
+        TraceEvent::WaitUntil { deadline: recorded, completed, .. } => {
+            assert_eq!(*recorded, deadline);
+            assert!(*completed >= deadline);
+        }
+        other => panic!("expected a wait_until, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_record_time_source_writes_trace_file() {
+    let path = std::env::temp_dir().join(format!("hourglass-record-{}.trace", std::process::id()));
+    let provider = SafeTimeProvider::new(TimeSource::Record(path.clone()));
+    provider.now();
+    provider.wait(Duration::milliseconds(1)).await;
+    drop(provider);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(contents.starts_with(trace::TRACE_HEADER));
+    let events = trace::read_trace(contents.as_bytes()).unwrap();
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn test_trace_lines_round_trip() {
+    let at = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+    let events = vec![
+        TraceEvent::Now {
+            seq: Some(0),
+            value: at("2024-01-01T09:00:00.000120Z"),
+            label: "src/billing.rs:42:23".to_string(),
+        },
+        TraceEvent::Wait {
+            seq: Some(12),
+            started: at("2024-01-01T09:00:00Z"),
+            duration: Duration::milliseconds(1500),
+            completed: at("2024-01-01T09:00:01.5012Z"),
+            label: "retry loop".to_string(),
+        },
+        TraceEvent::WaitUntil {
+            seq: None,
+            started: at("2024-01-01T09:00:01Z"),
+            deadline: at("2024-01-01T09:01:00Z"),
+            completed: at("2024-01-01T09:01:00.0009Z"),
+            label: String::new(),
+        },
+    ];
+
+    for event in events {
+        let line = event.to_string();
+        assert_eq!(line.parse::<TraceEvent>().unwrap(), event, "{}", line);
+    }
+    assert_eq!(
+        "wait 2024-01-01T09:00:00Z 1.5s 2024-01-01T09:00:01.5012Z retry loop".parse::<TraceEvent>().unwrap().label(),
+        "retry loop"
+    );
+    // Version 1 lines have no sequence number
+    assert_eq!(
+        "now 2024-01-01T09:00:00Z 42".parse::<TraceEvent>().unwrap(),
+        TraceEvent::Now { seq: None, value: at("2024-01-01T09:00:00Z"), label: "42".to_string() }
+    );
+}
+
+#[test]
+fn test_trace_parse_errors_report_line() {
+    let error = trace::read_trace("# hourglass trace v1\nnow 2024-01-01T00:00:00Z\nsleep 5s\n".as_bytes()).unwrap_err();
+    assert_eq!(error.line(), Some(3));
+    assert!(error.to_string().contains("unknown event 'sleep'"));
+
+    assert!("wait 2024-01-01T00:00:00Z 5 2024-01-01T00:00:05Z".parse::<TraceEvent>().is_err());
+}
+
+#[tokio::test]
+async fn test_sleeps_and_timeouts_are_not_recorded_as_waits() {
+    let buffer = SharedBuffer::default();
+    let provider = SafeTimeProvider::new_from_recorder(Arc::new(RecordingTimeProvider::new(buffer.clone())));
+
+    provider.sleep(Duration::milliseconds(5)).await;
+    assert!(buffer.events().is_empty());
+
+    // Only the clock read that places the deadline is traced
+    let result = provider.timeout(Duration::milliseconds(5), std::future::pending::<()>()).await;
+    assert!(result.is_err());
+    assert_eq!(buffer.events().iter().map(TraceEvent::kind).collect::<Vec<_>>(), vec!["now"]);
+}
+
+#[tokio::test]
+async fn test_overlapping_waits_are_numbered_in_start_order() {
+    let buffer = SharedBuffer::default();
+    let provider = SafeTimeProvider::new_from_recorder(Arc::new(RecordingTimeProvider::new(buffer.clone())));
+
+    provider.now();
+    tokio::join!(
+        provider.wait(Duration::milliseconds(30)),
+        provider.wait(Duration::milliseconds(10)),
+    );
+
+    // Written as they complete, numbered as they started
+    let waits: Vec<_> = buffer
+        .events()
+        .into_iter()
+        .filter_map(|event| match event {
+            TraceEvent::Wait { seq, duration, .. } => Some((seq, duration)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        waits,
+        vec![(Some(2), Duration::milliseconds(10)), (Some(1), Duration::milliseconds(30))]
+    );
+    assert_eq!(buffer.events()[0].seq(), Some(0));
+}
@@ -0,0 +1,132 @@
+use hourglass_rs::trace::TraceEvent;
+use hourglass_rs::{DivergencePolicy, ReplayTimeProvider, SafeTimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+mod common;
+use common::at;
+
+/// A trace of a job that reads the clock, backs off for 5s, then waits for the minute
+fn trace() -> Vec<TraceEvent> {
+    "# hourglass trace v1\n\
+     now 2024-01-01T09:00:00.25Z src/job.rs:10:20\n\
+     wait 2024-01-01T09:00:00.3Z 5s 2024-01-01T09:00:07.9Z src/job.rs:11:9\n\
+     now 2024-01-01T09:00:08Z src/job.rs:12:20\n\
+     wait_until 2024-01-01T09:00:08Z 2024-01-01T09:01:00Z 2024-01-01T09:01:00.5Z src/job.rs:13:9\n"
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.parse().unwrap())
+        .collect()
+}
+
+fn replay(policy: DivergencePolicy) -> (SafeTimeProvider, Arc<ReplayTimeProvider>) {
+    let replay = Arc::new(ReplayTimeProvider::new(trace(), policy));
+    (SafeTimeProvider::new_from_replay(replay.clone()), replay)
+}
+
+#[tokio::test]
+async fn test_replays_recorded_timeline() {
+    let (provider, replay) = replay(DivergencePolicy::Error);
+    assert!(provider.is_test_mode());
+    assert_eq!(replay.remaining(), 4);
+
+    assert_eq!(provider.now(), at("2024-01-01T09:00:00.25Z"));
+    provider.wait(Duration::seconds(5)).await;
+    // The wait overslept in production, and so does the replay
+    assert_eq!(provider.now(), at("2024-01-01T09:00:08Z"));
+    provider.wait_until(at("2024-01-01T09:01:00Z")).await;
+
+    assert_eq!(replay.remaining(), 0);
+    assert_eq!(replay.replayed(), 4);
+    assert!(!replay.has_diverged());
+    // Replayed waits return at once instead of counting as test waits
+    assert_eq!(provider.test_control().unwrap().wait_call_count(), 0);
+}
+
+#[tokio::test]
+async fn test_sleeps_follow_replayed_clock() {
+    let (provider, _replay) = replay(DivergencePolicy::Error);
+    provider.now();
+
+    let sleep = provider.sleep_until(at("2024-01-01T09:00:05Z"));
+    assert!(!sleep.is_elapsed());
+    provider.wait(Duration::seconds(5)).await;
+    assert!(sleep.is_elapsed());
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected 'wait 2024-01-01T09:00:00.300Z 5s")]
+async fn test_divergence_error_panics() {
+    let (provider, _replay) = replay(DivergencePolicy::Error);
+    provider.now();
+    provider.wait(Duration::seconds(10)).await;
+}
+
+#[tokio::test]
+async fn test_divergence_falls_back_to_test_clock() {
+    let (provider, replay) = replay(DivergencePolicy::FallBackToTestClock);
+    provider.now();
+
+    // Code now reads the clock twice in a row; the second read diverges
+    assert_eq!(provider.now(), at("2024-01-01T09:00:00.25Z"));
+    assert!(replay.has_diverged());
+    assert_eq!(replay.remaining(), 0);
+
+    provider.wait(Duration::seconds(5)).await;
+    assert_eq!(provider.now(), at("2024-01-01T09:00:05.25Z"));
+}
+
+#[tokio::test]
+async fn test_divergence_skips_to_matching_event() {
+    let (provider, replay) = replay(DivergencePolicy::Skip);
+
+    // The first read and the wait are no longer made
+    provider.wait_until(at("2024-01-01T09:01:00Z")).await;
+    assert_eq!(provider.now(), at("2024-01-01T09:01:00.5Z"));
+    assert!(replay.has_diverged());
+    assert_eq!(replay.replayed(), 1);
+    assert_eq!(replay.remaining(), 0);
+}
+
+#[tokio::test]
+async fn test_replay_time_source_reads_trace_file() {
+    let path = std::env::temp_dir().join(format!("hourglass-replay-{}.trace", std::process::id()));
+    let lines: Vec<String> = trace().iter().map(ToString::to_string).collect();
+    std::fs::write(&path, lines.join("\n")).unwrap();
+
+    let provider = SafeTimeProvider::new(TimeSource::Replay {
+        path: path.clone(),
+        on_divergence: DivergencePolicy::Error,
+    });
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(provider.now(), at("2024-01-01T09:00:00.25Z"));
+    provider.wait(Duration::seconds(5)).await;
+    assert_eq!(provider.now(), at("2024-01-01T09:00:08Z"));
+}
+
+/// Reads the clock around two overlapping waits, the shorter one started second
+async fn overlapping_waits(provider: &SafeTimeProvider) -> DateTime<Utc> {
+    provider.now();
+    tokio::join!(
+        provider.wait(Duration::milliseconds(30)),
+        provider.wait(Duration::milliseconds(10)),
+    );
+    provider.now()
+}
+
+#[tokio::test]
+async fn test_replays_recorded_overlapping_waits() {
+    let path = std::env::temp_dir().join(format!("hourglass-overlap-{}.trace", std::process::id()));
+    let recorder = SafeTimeProvider::new(TimeSource::Record(path.clone()));
+    let recorded_end = overlapping_waits(&recorder).await;
+    drop(recorder);
+
+    let replay = Arc::new(ReplayTimeProvider::load(&path, DivergencePolicy::Error).unwrap());
+    std::fs::remove_file(&path).unwrap();
+    let provider = SafeTimeProvider::new_from_replay(replay.clone());
+
+    assert_eq!(overlapping_waits(&provider).await, recorded_end);
+    assert!(!replay.has_diverged());
+    assert_eq!(replay.remaining(), 0);
+}
@@ -0,0 +1,76 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::Duration;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+
+mod common;
+use common::at;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor with no runtime behind it
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_test_provider_needs_no_runtime() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+
+    block_on(provider.wait(Duration::days(30)));
+    block_on(provider.sleep(Duration::hours(1)));
+    assert_eq!(provider.now(), at("2024-01-31T01:00:00Z"));
+
+    control.set_wait_mode(WaitMode::AutoAdvance);
+    block_on(provider.wait_labeled("accrual", Duration::hours(2)));
+    assert_eq!(provider.now(), at("2024-01-31T03:00:00Z"));
+    assert_eq!(control.waits_for("accrual")[0].task_id, None);
+}
+
+#[test]
+fn test_parked_wait_released_from_another_thread() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let driver = std::thread::spawn(move || {
+        while control.pending_count() == 0 {
+            std::thread::yield_now();
+        }
+        control.advance(Duration::hours(1));
+    });
+    block_on(provider.wait(Duration::hours(1)));
+    driver.join().unwrap();
+    assert_eq!(provider.now(), at("2024-01-01T01:00:00Z"));
+}
+
+#[cfg(not(feature = "tokio"))]
+#[test]
+fn test_real_wait_without_runtime() {
+    let provider = SafeTimeProvider::new(TimeSource::System);
+    let start = std::time::Instant::now();
+    block_on(provider.wait(Duration::milliseconds(50)));
+    assert!(start.elapsed() >= std::time::Duration::from_millis(50));
+
+    let mut sleep = std::pin::pin!(provider.sleep(Duration::hours(1)));
+    sleep.as_mut().reset(provider.now() + Duration::milliseconds(20));
+    block_on(sleep);
+    assert!(start.elapsed() >= std::time::Duration::from_millis(70));
+}
@@ -0,0 +1,113 @@
+// These tests run on tokio's paused clock
+#![cfg(feature = "tokio")]
+
+use hourglass_rs::{SafeTimeProvider, TimeSource};
+use chrono::Duration;
+
+mod common;
+use common::at;
+
+fn scaled(factor: f64) -> SafeTimeProvider {
+    SafeTimeProvider::new(TimeSource::Scaled {
+        start: at("2024-01-01T00:00:00Z"),
+        factor,
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clock_runs_at_factor() {
+    let provider = scaled(3600.0);
+    assert!(!provider.is_test_mode());
+    assert!(provider.test_control().is_none());
+    assert_eq!(provider.now(), at("2024-01-01T00:00:00Z"));
+
+    tokio::time::advance(std::time::Duration::from_secs(2)).await;
+    assert_eq!(provider.now(), at("2024-01-01T02:00:00Z"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_sleeps_scaled_real_time() {
+    let provider = scaled(3600.0);
+    let real_start = tokio::time::Instant::now();
+    let start = provider.instant();
+
+    provider.wait(Duration::hours(1)).await;
+    assert_eq!(real_start.elapsed(), std::time::Duration::from_secs(1));
+    assert_eq!(provider.instant() - start, Duration::hours(1));
+
+    provider.wait_until(at("2024-01-01T01:30:00Z")).await;
+    assert_eq!(real_start.elapsed(), std::time::Duration::from_millis(1500));
+    assert_eq!(provider.now(), at("2024-01-01T01:30:00Z"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_factor_changes_at_runtime() {
+    let provider = scaled(3600.0);
+    let control = provider.scale_control().unwrap();
+    assert_eq!(control.factor(), 3600.0);
+
+    tokio::time::advance(std::time::Duration::from_secs(1)).await;
+    control.set_factor(60.0);
+    // No jump when the speed changes
+    assert_eq!(provider.now(), at("2024-01-01T01:00:00Z"));
+
+    tokio::time::advance(std::time::Duration::from_secs(1)).await;
+    assert_eq!(provider.now(), at("2024-01-01T01:01:00Z"));
+
+    // New waits use the new speed
+    let real_start = tokio::time::Instant::now();
+    provider.wait(Duration::minutes(2)).await;
+    assert_eq!(real_start.elapsed(), std::time::Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pending_sleep_follows_factor_change() {
+    let provider = scaled(1.0);
+    let control = provider.scale_control().unwrap();
+    let real_start = tokio::time::Instant::now();
+
+    let p = provider.clone();
+    let sleeper = tokio::spawn(async move { p.wait(Duration::hours(1)).await });
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert!(!sleeper.is_finished());
+
+    // The remaining 59:59 of scaled time now pass in under a real second
+    control.set_factor(3600.0);
+    sleeper.await.unwrap();
+    assert!(real_start.elapsed() <= std::time::Duration::from_secs(2));
+    assert!(provider.now() >= at("2024-01-01T01:00:00Z"));
+}
+
+#[test]
+fn test_blocking_sleep_follows_factor_change() {
+    let provider = scaled(1.0);
+    let control = provider.scale_control().unwrap();
+    let real_start = std::time::Instant::now();
+
+    let speed_up = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        control.set_factor(360_000.0);
+    });
+    provider.sleep_blocking(Duration::hours(1));
+    speed_up.join().unwrap();
+    assert!(real_start.elapsed() < std::time::Duration::from_secs(5));
+    assert!(provider.now() >= at("2024-01-01T01:00:00Z"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_on_scaled_clock() {
+    let provider = scaled(60.0);
+    let mut interval = provider.interval(Duration::minutes(1));
+    let real_start = tokio::time::Instant::now();
+
+    for _ in 0..4 {
+        interval.tick().await;
+    }
+    assert_eq!(real_start.elapsed(), std::time::Duration::from_secs(3));
+}
+
+#[test]
+#[should_panic(expected = "scale factor must be positive")]
+fn test_rejects_zero_factor() {
+    scaled(0.0);
+}
@@ -0,0 +1,140 @@
+use hourglass_rs::schedule::{ParseScheduleError, Schedule};
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::{DateTime, Duration, Utc};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::Poll;
+
+mod common;
+use common::at;
+
+fn next(expression: &str, after: &str) -> DateTime<Utc> {
+    let schedule: Schedule = expression.parse().unwrap();
+    schedule.next_after(at(after)).expect("schedule should fire")
+}
+
+#[test]
+fn test_daily_at_midnight() {
+    assert_eq!(next("0 0 * * *", "2024-01-01T00:00:00Z"), at("2024-01-02T00:00:00Z"));
+    assert_eq!(next("0 0 * * *", "2024-01-01T13:45:10Z"), at("2024-01-02T00:00:00Z"));
+    assert_eq!(next("0 0 * * *", "2023-12-31T23:59:59Z"), at("2024-01-01T00:00:00Z"));
+}
+
+#[test]
+fn test_fields_ranges_steps_and_names() {
+    assert_eq!(next("*/15 9-17 * * MON-FRI", "2024-01-05T17:50:00Z"), at("2024-01-08T09:00:00Z"));
+    assert_eq!(next("30 8,12 * * *", "2024-01-01T08:30:00Z"), at("2024-01-01T12:30:00Z"));
+    assert_eq!(next("0 0 1 JAN,jul *", "2024-02-01T00:00:00Z"), at("2024-07-01T00:00:00Z"));
+    assert_eq!(next("0 6/6 * * *", "2024-01-01T07:00:00Z"), at("2024-01-01T12:00:00Z"));
+    assert_eq!(next("0 0 * * 7", "2024-01-01T00:00:00Z"), at("2024-01-07T00:00:00Z"));
+}
+
+#[test]
+fn test_six_field_seconds() {
+    assert_eq!(next("*/20 * * * * *", "2024-01-01T00:00:41.5Z"), at("2024-01-01T00:01:00Z"));
+    assert_eq!(next("15 30 2 * * *", "2024-01-01T02:30:15Z"), at("2024-01-02T02:30:15Z"));
+}
+
+#[test]
+fn test_last_day_and_last_weekday() {
+    assert_eq!(next("0 0 L * *", "2024-02-10T00:00:00Z"), at("2024-02-29T00:00:00Z"));
+    assert_eq!(next("0 0 L * *", "2023-02-10T00:00:00Z"), at("2023-02-28T00:00:00Z"));
+    assert_eq!(next("0 0 L-1 * *", "2024-04-01T00:00:00Z"), at("2024-04-29T00:00:00Z"));
+    // 2024-03-31 is a Sunday, so the last business day is Friday the 29th
+    assert_eq!(next("0 0 LW * *", "2024-03-01T00:00:00Z"), at("2024-03-29T00:00:00Z"));
+}
+
+#[test]
+fn test_nearest_weekday() {
+    // 2024-06-15 is a Saturday
+    assert_eq!(next("0 0 15W * *", "2024-06-01T00:00:00Z"), at("2024-06-14T00:00:00Z"));
+    // 2024-09-01 is a Sunday; 1W must stay in September
+    assert_eq!(next("0 0 1W * *", "2024-08-31T00:00:00Z"), at("2024-09-02T00:00:00Z"));
+    // 2024-06-01 is a Saturday; 1W moves forward to Monday the 3rd
+    assert_eq!(next("0 0 1W * *", "2024-05-31T00:00:00Z"), at("2024-06-03T00:00:00Z"));
+}
+
+#[test]
+fn test_nth_and_last_weekday_of_month() {
+    // Second Monday of January 2024
+    assert_eq!(next("0 0 * * 1#2", "2024-01-01T00:00:00Z"), at("2024-01-08T00:00:00Z"));
+    // Last Friday of January 2024
+    assert_eq!(next("0 0 * * 5L", "2024-01-01T00:00:00Z"), at("2024-01-26T00:00:00Z"));
+    assert_eq!(next("0 0 * * FRIL", "2024-01-27T00:00:00Z"), at("2024-02-23T00:00:00Z"));
+}
+
+#[test]
+fn test_restricted_day_fields_are_ored() {
+    // The 13th or any Friday
+    assert_eq!(next("0 0 13 * FRI", "2024-01-01T00:00:00Z"), at("2024-01-05T00:00:00Z"));
+    assert_eq!(next("0 0 13 * FRI", "2024-01-12T00:00:00Z"), at("2024-01-13T00:00:00Z"));
+}
+
+#[test]
+fn test_never_firing_schedule() {
+    let schedule: Schedule = "0 0 30 2 *".parse().unwrap();
+    assert_eq!(schedule.next_after(at("2024-01-01T00:00:00Z")), None);
+}
+
+#[test]
+fn test_includes_and_upcoming() {
+    let schedule: Schedule = "0 12 * * *".parse().unwrap();
+    assert!(schedule.includes(at("2024-05-05T12:00:00Z")));
+    assert!(!schedule.includes(at("2024-05-05T12:00:01Z")));
+
+    let upcoming: Vec<_> = schedule.upcoming(at("2024-05-05T12:00:00Z")).take(2).collect();
+    assert_eq!(upcoming, vec![at("2024-05-06T12:00:00Z"), at("2024-05-07T12:00:00Z")]);
+    assert_eq!(schedule.to_string(), "0 12 * * *");
+}
+
+#[test]
+fn test_parse_errors() {
+    let error: ParseScheduleError = "0 0 * *".parse::<Schedule>().unwrap_err();
+    assert!(error.to_string().contains("expected 5 or 6 fields"));
+
+    assert!("60 * * * *".parse::<Schedule>().is_err());
+    assert!("0 0 32 * *".parse::<Schedule>().is_err());
+    assert!("0 0 * 13 *".parse::<Schedule>().is_err());
+    assert!("0 0 * * 1#6".parse::<Schedule>().is_err());
+    assert!("*/0 * * * *".parse::<Schedule>().is_err());
+    assert!("0 5-1 * * *".parse::<Schedule>().is_err());
+    assert!("0 0 * * L".parse::<Schedule>().is_err());
+}
+
+#[tokio::test]
+async fn test_wait_next_on_test_clock() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-30T15:00:00Z")));
+    let schedule: Schedule = "0 0 L * *".parse().unwrap();
+
+    assert_eq!(schedule.wait_next(&provider).await, Some(at("2024-01-31T00:00:00Z")));
+    assert_eq!(schedule.wait_next(&provider).await, Some(at("2024-02-29T00:00:00Z")));
+    assert_eq!(provider.now(), at("2024-02-29T00:00:00Z"));
+}
+
+#[tokio::test]
+async fn test_firings_catch_up_after_advance() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T12:00:00Z")));
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let schedule: Schedule = "0 0 * * *".parse().unwrap();
+    let mut firings = schedule.firings(&provider);
+    assert_eq!(firings.peek(), Some(at("2024-01-02T00:00:00Z")));
+
+    control.advance(Duration::days(3));
+    let mut fired = Vec::new();
+    loop {
+        let poll = std::future::poll_fn(|cx| Poll::Ready(Pin::new(&mut firings).poll_next(cx))).await;
+        match poll {
+            Poll::Ready(Some(time)) => fired.push(time),
+            _ => break,
+        }
+    }
+    assert_eq!(
+        fired,
+        vec![at("2024-01-02T00:00:00Z"), at("2024-01-03T00:00:00Z"), at("2024-01-04T00:00:00Z")]
+    );
+
+    control.advance(Duration::days(1));
+    assert_eq!(firings.wait_next().await, Some(at("2024-01-05T00:00:00Z")));
+}
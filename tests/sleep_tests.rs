@@ -0,0 +1,132 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource};
+use chrono::{DateTime, Duration, Utc};
+use std::future::Future;
+
+mod common;
+use common::parked_provider;
+
+#[tokio::test]
+async fn test_sleep_reports_deadline() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+    let sleep = provider.sleep(Duration::hours(2));
+
+    assert_eq!(sleep.deadline(), "2024-01-01T02:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    assert!(!sleep.is_elapsed());
+
+    sleep.await;
+    assert_eq!(provider.now(), "2024-01-01T02:00:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+#[tokio::test]
+async fn test_sleep_is_elapsed_after_advance() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+
+    let sleep = provider.sleep(Duration::minutes(10));
+    assert!(!sleep.is_elapsed());
+
+    control.advance(Duration::minutes(10));
+    assert!(sleep.is_elapsed());
+    sleep.await;
+}
+
+#[tokio::test]
+async fn test_sleep_reset_moves_deadline() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+
+    let sleep = provider.sleep(Duration::hours(1));
+    tokio::pin!(sleep);
+
+    // Register the original deadline, then push it back
+    assert!(futures_poll(sleep.as_mut()).await.is_pending());
+    sleep.as_mut().reset("2024-01-01T03:00:00Z".parse().unwrap());
+
+    control.advance(Duration::hours(2));
+    assert!(futures_poll(sleep.as_mut()).await.is_pending());
+
+    control.advance(Duration::hours(1));
+    sleep.as_mut().await;
+    assert!(sleep.is_elapsed());
+
+    // A completed sleep can be re-armed
+    sleep.as_mut().reset("2024-01-01T04:00:00Z".parse().unwrap());
+    assert!(!sleep.is_elapsed());
+    control.advance(Duration::hours(1));
+    sleep.await;
+}
+
+#[tokio::test]
+async fn test_sleep_reset_in_select_loop() {
+    // An idle timeout that is pushed back every time a message arrives
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<u32>();
+
+    let p = provider.clone();
+    let watcher = tokio::spawn(async move {
+        let idle = p.sleep(Duration::minutes(5));
+        tokio::pin!(idle);
+        let mut received = 0;
+        loop {
+            tokio::select! {
+                Some(_) = rx.recv() => {
+                    received += 1;
+                    idle.as_mut().reset(p.now() + Duration::minutes(5));
+                }
+                _ = &mut idle => return (received, p.now()),
+            }
+        }
+    });
+
+    for _ in 0..3 {
+        tokio::task::yield_now().await;
+        control.advance(Duration::minutes(4));
+        tx.send(1).unwrap();
+    }
+    tokio::task::yield_now().await;
+    control.advance(Duration::minutes(5));
+
+    let (received, fired_at) = watcher.await.unwrap();
+    assert_eq!(received, 3);
+    assert_eq!(fired_at, "2024-01-01T00:17:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+#[tokio::test]
+async fn test_system_sleep_reset() {
+    let provider = SafeTimeProvider::new(TimeSource::System);
+    let started = std::time::Instant::now();
+
+    let sleep = provider.sleep(Duration::seconds(60));
+    tokio::pin!(sleep);
+    sleep.as_mut().reset(provider.now() + Duration::milliseconds(20));
+    sleep.as_mut().await;
+
+    assert!(sleep.is_elapsed());
+    assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_concurrent_waits_add_up_in_immediate_mode() {
+    let provider = SafeTimeProvider::new(
+        TimeSource::Test("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+
+    // Each wait takes its deadline from the clock when it starts
+    tokio::join!(provider.wait(Duration::hours(1)), provider.wait(Duration::hours(2)));
+    assert_eq!(provider.now(), "2024-01-01T03:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+    // Sleeps fix their deadline up front, so together they end at the latest one
+    let first = provider.sleep(Duration::hours(1));
+    let second = provider.sleep(Duration::hours(2));
+    tokio::join!(first, second);
+    assert_eq!(provider.now(), "2024-01-01T05:00:00Z".parse::<DateTime<Utc>>().unwrap());
+}
+
+/// Poll a future exactly once
+async fn futures_poll<F: Future + Unpin>(mut future: F) -> std::task::Poll<F::Output> {
+    std::future::poll_fn(|cx| std::task::Poll::Ready(std::pin::Pin::new(&mut future).poll(cx))).await
+}
@@ -0,0 +1,126 @@
+use hourglass_rs::{ClockEvent, SafeTimeProvider, TimeSource, WaitMode};
+use chrono::Duration;
+
+mod common;
+use common::at;
+
+fn test_provider() -> SafeTimeProvider {
+    SafeTimeProvider::new(TimeSource::Test(at("2024-01-31T00:00:00Z")))
+}
+
+#[tokio::test]
+async fn test_branch_and_rewind() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    provider.wait_labeled("setup", Duration::hours(1)).await;
+
+    let checkpoint = control.snapshot();
+    assert_eq!(checkpoint.current_time(), at("2024-01-31T01:00:00Z"));
+    assert_eq!(checkpoint.wait_call_count(), 1);
+
+    // Month-end path
+    provider.wait_labeled("month-end", Duration::days(1)).await;
+    control.advance(Duration::days(30));
+    assert_eq!(control.wait_call_count(), 2);
+
+    control.restore(&checkpoint);
+    assert_eq!(provider.now(), at("2024-01-31T01:00:00Z"));
+    assert_eq!(control.total_waited(), Duration::hours(1));
+    assert_eq!(control.wait_history(), checkpoint.wait_history());
+    assert!(control.waits_for("month-end").is_empty());
+
+    // Overdue path from the same point
+    provider.wait_labeled("overdue", Duration::days(50)).await;
+    assert_eq!(provider.now(), at("2024-03-21T01:00:00Z"));
+    assert_eq!(control.wait_call_count(), 2);
+    assert_eq!(control.waits_for("overdue").len(), 1);
+}
+
+#[tokio::test]
+async fn test_restore_keeps_sleepers_parked() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let p = provider.clone();
+    let before = tokio::spawn(async move { p.wait_labeled("before", Duration::hours(2)).await });
+    while control.pending_count() < 1 {
+        tokio::task::yield_now().await;
+    }
+    let checkpoint = control.snapshot();
+    assert_eq!(checkpoint.pending_timers().len(), 1);
+
+    let p = provider.clone();
+    let after = tokio::spawn(async move { p.wait_labeled("after", Duration::hours(1)).await });
+    while control.pending_count() < 2 {
+        tokio::task::yield_now().await;
+    }
+    control.advance(Duration::minutes(30));
+
+    control.restore(&checkpoint);
+    assert_eq!(provider.now(), at("2024-01-31T00:00:00Z"));
+    // Both sleepers stay on their original deadlines
+    assert_eq!(control.pending_count(), 2);
+    assert_eq!(control.next_deadline(), Some(at("2024-01-31T01:00:00Z")));
+
+    control.advance(Duration::hours(2));
+    before.await.unwrap();
+    after.await.unwrap();
+
+    // Only the wait from before the snapshot is in the restored history
+    let history = control.wait_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].label.as_deref(), Some("before"));
+    assert_eq!(history[0].completed_at, Some(at("2024-01-31T02:00:00Z")));
+}
+
+#[test]
+fn test_restore_notifies_observers() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    let start = provider.instant();
+    let checkpoint = control.snapshot();
+    control.advance(Duration::days(1));
+
+    let mut events = provider.subscribe();
+    control.restore(&checkpoint);
+    assert_eq!(
+        events.try_next_event(),
+        Some(ClockEvent::WentBackwards { from: at("2024-02-01T00:00:00Z"), to: at("2024-01-31T00:00:00Z") })
+    );
+    // The monotonic clock keeps going
+    assert_eq!(start.elapsed(&provider), Duration::days(1));
+}
+
+#[tokio::test]
+async fn test_snapshot_is_consistent_while_the_clock_moves() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let sleepers: Vec<_> = (1..=200)
+        .map(|minutes| {
+            let p = provider.clone();
+            tokio::spawn(async move { p.wait(Duration::minutes(minutes)).await })
+        })
+        .collect();
+    while control.pending_count() < 200 {
+        tokio::task::yield_now().await;
+    }
+
+    let driver = std::thread::spawn(move || {
+        for _ in 0..200 {
+            control.advance(Duration::minutes(1));
+        }
+    });
+    let control = provider.test_control().unwrap();
+    while !driver.is_finished() {
+        let snapshot = control.snapshot();
+        // Sleepers due at the snapshot time have already been released
+        assert!(snapshot.pending_timers().iter().all(|timer| timer.deadline > snapshot.current_time()));
+    }
+    driver.join().unwrap();
+    for sleeper in sleepers {
+        sleeper.await.unwrap();
+    }
+}
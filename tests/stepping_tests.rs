@@ -0,0 +1,90 @@
+use chrono::Duration;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::{at, parked_provider};
+
+#[tokio::test]
+async fn test_advance_to_next_timer() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    assert_eq!(control.advance_to_next_timer(), None);
+
+    let mut handles = Vec::new();
+    for minutes in [30, 10] {
+        let p = provider.clone();
+        handles.push(tokio::spawn(async move {
+            p.wait(Duration::minutes(minutes)).await;
+            p.now()
+        }));
+    }
+    while control.pending_count() < 2 {
+        tokio::task::yield_now().await;
+    }
+
+    assert_eq!(control.advance_to_next_timer(), Some(at("2024-01-01T00:10:00Z")));
+    assert_eq!(provider.now(), at("2024-01-01T00:10:00Z"));
+    assert_eq!(handles.remove(1).await.unwrap(), at("2024-01-01T00:10:00Z"));
+    assert_eq!(control.pending_count(), 1);
+
+    assert_eq!(control.advance_to_next_timer(), Some(at("2024-01-01T00:30:00Z")));
+    assert_eq!(handles.remove(0).await.unwrap(), at("2024-01-01T00:30:00Z"));
+    assert_eq!(control.advance_to_next_timer(), None);
+}
+
+#[tokio::test]
+async fn test_run_until_fires_timers_in_order() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    for (name, hours) in [("every-3h", 3), ("every-2h", 2)] {
+        let p = provider.clone();
+        let log = log.clone();
+        tokio::spawn(async move {
+            loop {
+                p.wait(Duration::hours(hours)).await;
+                log.lock().unwrap().push((name, p.now()));
+            }
+        });
+    }
+
+    control.run_until(at("2024-01-01T07:00:00Z")).await;
+    assert_eq!(provider.now(), at("2024-01-01T07:00:00Z"));
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            ("every-2h", at("2024-01-01T02:00:00Z")),
+            ("every-3h", at("2024-01-01T03:00:00Z")),
+            ("every-2h", at("2024-01-01T04:00:00Z")),
+            ("every-3h", at("2024-01-01T06:00:00Z")),
+            ("every-2h", at("2024-01-01T06:00:00Z")),
+        ]
+    );
+    assert_eq!(control.next_deadline(), Some(at("2024-01-01T08:00:00Z")));
+}
+
+#[tokio::test]
+async fn test_run_until_idle_stops_at_bound() {
+    let provider = parked_provider();
+    let control = provider.test_control().unwrap();
+
+    let p = provider.clone();
+    let retries = tokio::spawn(async move {
+        for minutes in [5, 10, 20] {
+            p.wait(Duration::minutes(minutes)).await;
+        }
+        p.now()
+    });
+    let p = provider.clone();
+    tokio::spawn(async move { p.wait_labeled("nightly", Duration::days(1)).await });
+
+    control.run_until_idle(Duration::hours(1)).await;
+    assert_eq!(retries.await.unwrap(), at("2024-01-01T00:35:00Z"));
+    // The clock stays at the last deadline that fired
+    assert_eq!(provider.now(), at("2024-01-01T00:35:00Z"));
+
+    let pending = control.pending_timers();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].label.as_deref(), Some("nightly"));
+}
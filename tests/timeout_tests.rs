@@ -0,0 +1,85 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource, WaitMode};
+use chrono::Duration;
+
+mod common;
+use common::at;
+
+#[tokio::test]
+async fn test_timeout_returns_output_when_future_finishes_first() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+
+    let result = provider.timeout(Duration::hours(1), async { 42 }).await;
+    assert_eq!(result, Ok(42));
+
+    // A wait that finishes inside the deadline also wins
+    let p = provider.clone();
+    let result = provider
+        .timeout(Duration::hours(1), async move { p.wait(Duration::minutes(30)).await })
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(provider.now(), at("2024-01-01T00:30:00Z"));
+}
+
+#[tokio::test]
+async fn test_timeout_triggered_by_advance() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let p = provider.clone();
+    let handle = tokio::spawn(async move { p.timeout(Duration::seconds(30), rx).await });
+
+    // The timeout never moves the clock on its own, even in immediate mode
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+    assert_eq!(provider.now(), at("2024-01-01T00:00:00Z"));
+    assert_eq!(control.wait_call_count(), 0);
+
+    control.advance(Duration::seconds(30));
+    let elapsed = handle.await.unwrap().unwrap_err();
+    assert_eq!(elapsed.deadline(), at("2024-01-01T00:00:30Z"));
+}
+
+#[tokio::test]
+async fn test_timeout_loses_to_longer_wait() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let p = provider.clone();
+    let handle = tokio::spawn(async move {
+        let inner = p.clone();
+        p.timeout_at(at("2024-01-01T01:00:00Z"), async move {
+            inner.wait(Duration::hours(2)).await
+        })
+        .await
+    });
+
+    tokio::task::yield_now().await;
+    control.advance(Duration::hours(1));
+    assert!(handle.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn test_timeout_fires_on_auto_advance() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    provider.test_control().unwrap().set_wait_mode(WaitMode::AutoAdvance);
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let result = provider.timeout(Duration::minutes(5), rx).await;
+    assert!(result.is_err());
+    assert_eq!(provider.now(), at("2024-01-01T00:05:00Z"));
+}
+
+#[tokio::test]
+async fn test_timeout_with_system_time() {
+    let provider = SafeTimeProvider::new(TimeSource::System);
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let started = std::time::Instant::now();
+    let result = provider.timeout(Duration::milliseconds(20), rx).await;
+
+    assert!(result.is_err());
+    assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+    assert!(result.unwrap_err().to_string().contains("has elapsed"));
+}
@@ -0,0 +1,116 @@
+use hourglass_rs::WaitMode;
+use chrono::Duration;
+
+mod common;
+use common::{at, test_provider};
+
+#[tokio::test]
+async fn test_history_records_labels() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+
+    provider.wait_labeled("accrual", Duration::hours(1)).await;
+    provider.wait(Duration::minutes(5)).await;
+    provider.wait_labeled("billing", Duration::minutes(30)).await;
+    provider.wait_until_labeled("accrual", at("2024-01-01T03:00:00Z")).await;
+
+    let history = control.wait_history();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[1].label, None);
+    assert_eq!(history[2].label.as_deref(), Some("billing"));
+
+    let accrual = control.waits_for("accrual");
+    assert_eq!(accrual.len(), 2);
+    assert_eq!(accrual[0].started_at, at("2024-01-01T00:00:00Z"));
+    assert_eq!(accrual[0].deadline, at("2024-01-01T01:00:00Z"));
+    assert_eq!(accrual[0].completed_at, Some(at("2024-01-01T01:00:00Z")));
+    assert_eq!(accrual[1].started_at, at("2024-01-01T01:35:00Z"));
+    assert_eq!(accrual[1].duration, Duration::minutes(85));
+    assert_eq!(accrual[1].completed_at, Some(at("2024-01-01T03:00:00Z")));
+
+    assert_eq!(control.wait_call_count(), 4);
+    assert!(control.waits_for("settlement").is_empty());
+}
+
+#[tokio::test]
+async fn test_history_tracks_pending_waits_per_task() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let mut handles = Vec::new();
+    for (label, minutes) in [("accrual", 10), ("billing", 20)] {
+        let p = provider.clone();
+        handles.push(tokio::spawn(async move { p.wait_labeled(label, Duration::minutes(minutes)).await }));
+    }
+    while control.wait_call_count() < 2 {
+        tokio::task::yield_now().await;
+    }
+
+    control.advance(Duration::minutes(15));
+    handles.remove(0).await.unwrap();
+
+    let accrual = &control.waits_for("accrual")[0];
+    let billing = &control.waits_for("billing")[0];
+    assert_eq!(accrual.completed_at, Some(at("2024-01-01T00:15:00Z")));
+    assert_eq!(billing.completed_at, None);
+    if cfg!(feature = "tokio") {
+        assert!(accrual.task_id.is_some());
+        assert_ne!(accrual.task_id, billing.task_id);
+    }
+
+    control.advance(Duration::minutes(15));
+    handles.remove(0).await.unwrap();
+    assert_eq!(control.waits_for("billing")[0].completed_at, Some(at("2024-01-01T00:30:00Z")));
+}
+
+#[tokio::test]
+async fn test_reset_clears_history() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+
+    let p = provider.clone();
+    let pending = tokio::spawn(async move { p.wait_labeled("slow", Duration::hours(2)).await });
+    while control.wait_call_count() < 1 {
+        tokio::task::yield_now().await;
+    }
+    control.reset_wait_tracking();
+    assert!(control.wait_history().is_empty());
+
+    control.set_wait_mode(WaitMode::Immediate);
+    provider.wait_labeled("fast", Duration::hours(1)).await;
+    control.advance(Duration::hours(1));
+    pending.await.unwrap();
+
+    // The wait started before the reset does not leak into the new history
+    let history = control.wait_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].label.as_deref(), Some("fast"));
+    assert_eq!(history[0].completed_at, Some(at("2024-01-01T01:00:00Z")));
+}
+
+#[tokio::test]
+async fn test_history_limit_drops_oldest_entries() {
+    let provider = test_provider();
+    let control = provider.test_control().unwrap();
+    control.set_wait_history_limit(3);
+
+    for i in 0..5 {
+        provider.wait_labeled(&format!("wait-{i}"), Duration::minutes(1)).await;
+        control.advance(Duration::minutes(1));
+    }
+    let labels: Vec<_> = control.wait_history().into_iter().map(|wait| wait.label.unwrap()).collect();
+    assert_eq!(labels, ["wait-2", "wait-3", "wait-4"]);
+    assert_eq!(control.wait_call_count(), 5);
+
+    // The clock changes that are kept still line up with their waits
+    let timeline = hourglass_rs::assert::timeline(&control);
+    let kinds: Vec<_> = timeline
+        .lines()
+        .skip(1)
+        .map(|line| if line.contains("wait") { "wait" } else { "advance" })
+        .collect();
+    assert_eq!(kinds, ["wait", "advance", "wait", "advance", "wait", "advance"]);
+    assert!(timeline.lines().nth(1).unwrap().contains("wait-2"));
+}
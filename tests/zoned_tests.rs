@@ -0,0 +1,73 @@
+use hourglass_rs::{SafeTimeProvider, TimeSource, Tz, WaitMode};
+use chrono::{NaiveDateTime, Timelike};
+
+mod common;
+use common::at;
+
+fn local(time: &str) -> NaiveDateTime {
+    time.parse().unwrap()
+}
+
+#[test]
+fn test_now_in_follows_dst() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-03-10T06:59:00Z")));
+    let control = provider.test_control().unwrap();
+    let new_york = provider.zoned(Tz::America__New_York);
+
+    assert_eq!(new_york.now().naive_local(), local("2024-03-10T01:59:00"));
+
+    // 02:00 EST jumps straight to 03:00 EDT
+    control.set(at("2024-03-10T07:00:00Z"));
+    assert_eq!(new_york.now().naive_local(), local("2024-03-10T03:00:00"));
+    assert_eq!(provider.now_in(&Tz::Europe__London).hour(), 7);
+}
+
+#[test]
+fn test_resolve_local_skipped_and_repeated_hours() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-01-01T00:00:00Z")));
+    let new_york = provider.zoned(Tz::America__New_York);
+
+    // Ordinary time
+    assert_eq!(new_york.resolve_local(local("2024-06-03T17:00:00")), at("2024-06-03T21:00:00Z"));
+
+    // 02:30 never happens on spring-forward day; the clock first reads later at 03:00 EDT
+    assert_eq!(new_york.resolve_local(local("2024-03-10T02:30:15")), at("2024-03-10T07:00:00Z"));
+
+    // 01:30 happens twice on fall-back day; the first one is in EDT
+    assert_eq!(new_york.resolve_local(local("2024-11-03T01:30:00")), at("2024-11-03T05:30:00Z"));
+}
+
+#[tokio::test]
+async fn test_wait_until_local_in_immediate_mode() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-03-09T22:00:00Z")));
+    let new_york = provider.zoned(Tz::America__New_York);
+
+    new_york.wait_until_local(local("2024-03-10T02:30:00")).await;
+    assert_eq!(provider.now(), at("2024-03-10T07:00:00Z"));
+    assert_eq!(new_york.now().naive_local(), local("2024-03-10T03:00:00"));
+}
+
+#[tokio::test]
+async fn test_wait_until_local_released_by_set_across_fall_back() {
+    let provider = SafeTimeProvider::new(TimeSource::Test(at("2024-11-03T04:00:00Z")));
+    let control = provider.test_control().unwrap();
+    control.set_wait_mode(WaitMode::Parked);
+    let new_york = provider.zoned(Tz::America__New_York);
+
+    let zoned = new_york.clone();
+    let handle = tokio::spawn(async move {
+        zoned.wait_until_local(local("2024-11-03T01:30:00")).await;
+        zoned.now()
+    });
+    tokio::task::yield_now().await;
+
+    control.set(at("2024-11-03T05:29:00Z"));
+    tokio::task::yield_now().await;
+    assert!(!handle.is_finished());
+
+    // The first 01:30, still in daylight time
+    control.set(at("2024-11-03T05:30:00Z"));
+    let woke_at = handle.await.unwrap();
+    assert_eq!(woke_at.naive_local(), local("2024-11-03T01:30:00"));
+    assert_eq!(woke_at.offset().to_string(), "EDT");
+}